@@ -0,0 +1,371 @@
+use crate::ApiError;
+use axum::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// Maximum number of cached responses, expired and then the oldest responses are removed first
+const MAX_ENTRIES: usize = 4096;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    body: Arc<str>,
+    etag: Arc<str>,
+    fetched: Instant,
+    ttl: Duration,
+}
+
+/// A serialized response, with the headers needed for caching by clients
+#[derive(Debug, Clone)]
+pub struct CachedBody {
+    body: Arc<str>,
+    etag: Arc<str>,
+    max_age: Duration,
+    stale_while_revalidate: Duration,
+}
+
+impl CachedBody {
+    /// Build the response, without body if the client already has the current version
+    pub fn respond(self, request_headers: &HeaderMap) -> Response {
+        let not_modified = request_headers
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == &*self.etag
+            });
+
+        let cache_control = format!(
+            "public, max-age={}, stale-while-revalidate={}",
+            self.max_age.as_secs(),
+            self.stale_while_revalidate.as_secs()
+        );
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(&cache_control) {
+            headers.insert(CACHE_CONTROL, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.etag) {
+            headers.insert(ETAG, value);
+        }
+
+        if not_modified {
+            (StatusCode::NOT_MODIFIED, headers).into_response()
+        } else {
+            headers.insert(
+                axum::http::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            (headers, self.body.to_string()).into_response()
+        }
+    }
+}
+
+enum Lookup {
+    Fresh(CachedBody),
+    Stale(CachedBody),
+    Missing,
+}
+
+/// Result of a fetch, shared with every request that waited for it
+type FlightResult = Option<Result<CachedBody, Arc<ApiError>>>;
+
+/// Removes a fetch from the in-flight fetches once its task ends, including when the fetch panics
+struct FlightGuard {
+    cache: Arc<ResponseCache>,
+    key: String,
+}
+
+impl Drop for FlightGuard {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.cache.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+/// In-process cache of serialized api responses
+///
+/// Concurrent requests for the same key share a single scrape, and expired responses are
+/// served for a while longer while they are refreshed in the background.
+#[derive(Debug)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, Entry>>,
+    in_flight: Mutex<HashMap<String, watch::Receiver<FlightResult>>>,
+    stale_while_revalidate: Duration,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10 * 60))
+    }
+}
+
+impl ResponseCache {
+    /// Create a cache that serves expired responses for up to `stale_while_revalidate`
+    pub fn new(stale_while_revalidate: Duration) -> Self {
+        ResponseCache {
+            entries: Mutex::default(),
+            in_flight: Mutex::default(),
+            stale_while_revalidate,
+        }
+    }
+
+    /// Get the cached response for `key`, or fetch it if it isn't cached
+    pub async fn get<T, F, Fut>(
+        self: &Arc<Self>,
+        key: String,
+        ttl: Duration,
+        fetch: F,
+    ) -> Result<CachedBody, ApiError>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        match self.lookup(&key) {
+            Lookup::Fresh(body) => return Ok(body),
+            Lookup::Stale(body) => {
+                self.start_fetch(key, ttl, fetch);
+                return Ok(body);
+            }
+            Lookup::Missing => {}
+        }
+
+        let mut flight = self.start_fetch(key, ttl, fetch);
+        let result = flight
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| ApiError::FetchAborted)?
+            .clone();
+        match result {
+            Some(Ok(body)) => Ok(body),
+            Some(Err(error)) => Err(ApiError::Shared(error)),
+            None => Err(ApiError::FetchAborted),
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Lookup {
+        let entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get(key) else {
+            return Lookup::Missing;
+        };
+        let age = entry.fetched.elapsed();
+        let body = CachedBody {
+            body: entry.body.clone(),
+            etag: entry.etag.clone(),
+            max_age: entry.ttl.saturating_sub(age),
+            stale_while_revalidate: self.stale_while_revalidate,
+        };
+        if age < entry.ttl {
+            Lookup::Fresh(body)
+        } else if age < entry.ttl + self.stale_while_revalidate {
+            Lookup::Stale(body)
+        } else {
+            Lookup::Missing
+        }
+    }
+
+    /// Fetch the response in the background, unless a fetch for the key is already running
+    ///
+    /// The fetch isn't tied to the request that started it, so it completes for the other
+    /// waiting requests even if that request is cancelled.
+    fn start_fetch<T, F, Fut>(
+        self: &Arc<Self>,
+        key: String,
+        ttl: Duration,
+        fetch: F,
+    ) -> watch::Receiver<FlightResult>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(flight) = in_flight.get(&key) {
+            return flight.clone();
+        }
+        let (sender, flight) = watch::channel(None);
+        in_flight.insert(key.clone(), flight.clone());
+
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let _guard = FlightGuard {
+                cache: cache.clone(),
+                key: key.clone(),
+            };
+            debug!(key, "fetching response");
+            let result = cache.fetch(key.clone(), ttl, fetch).await;
+            if let Err(error) = &result {
+                warn!(?error, key, "failed to fetch response");
+            }
+            sender.send_replace(Some(result.map_err(Arc::new)));
+        });
+        flight
+    }
+
+    async fn fetch<T, F, Fut>(
+        &self,
+        key: String,
+        ttl: Duration,
+        fetch: F,
+    ) -> Result<CachedBody, ApiError>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let response = fetch().await?;
+        let body: Arc<str> = serde_json::to_string(&response)
+            .map_err(ApiError::Serialize)?
+            .into();
+        let mut etag = String::from("\"");
+        for byte in Sha256::digest(body.as_bytes()) {
+            let _ = write!(etag, "{byte:02x}");
+        }
+        etag.push('"');
+        let etag: Arc<str> = etag.into();
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            let stale = self.stale_while_revalidate;
+            entries.retain(|_, entry| entry.fetched.elapsed() < entry.ttl + stale);
+            // evict the oldest responses if there are still too many that could be served
+            if entries.len() >= MAX_ENTRIES {
+                let mut by_age: Vec<_> = entries
+                    .iter()
+                    .map(|(key, entry)| (entry.fetched, key.clone()))
+                    .collect();
+                by_age.sort_unstable();
+                for (_, key) in by_age.into_iter().take(entries.len() + 1 - MAX_ENTRIES) {
+                    entries.remove(&key);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                body: body.clone(),
+                etag: etag.clone(),
+                fetched: Instant::now(),
+                ttl,
+            },
+        );
+        Ok(CachedBody {
+            body,
+            etag,
+            max_age: ttl,
+            stale_while_revalidate: self.stale_while_revalidate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn test_concurrent_misses_share_fetch() {
+        let cache = Arc::new(ResponseCache::default());
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let fetch = || {
+            let fetches = fetches.clone();
+            || async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(fetches.fetch_add(1, Ordering::SeqCst))
+            }
+        };
+
+        let (first, second) = tokio::join!(
+            cache.get("key".into(), TTL, fetch()),
+            cache.get("key".into(), TTL, fetch()),
+        );
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(&*first.unwrap().body, "0");
+        assert_eq!(&*second.unwrap().body, "0");
+    }
+
+    #[tokio::test]
+    async fn test_serve_stale_while_refreshing() {
+        let cache = Arc::new(ResponseCache::new(TTL));
+        let body = cache
+            .get("key".into(), Duration::ZERO, || async { Ok(1) })
+            .await
+            .unwrap();
+        assert_eq!(&*body.body, "1");
+
+        let refresh = Arc::new(Notify::new());
+        let wait_for_refresh = refresh.clone();
+        let body = cache
+            .get("key".into(), Duration::ZERO, || async move {
+                wait_for_refresh.notified().await;
+                Ok(2)
+            })
+            .await
+            .unwrap();
+        assert_eq!(&*body.body, "1");
+        assert!(cache.in_flight.lock().unwrap().contains_key("key"));
+
+        refresh.notify_one();
+        while cache.in_flight.lock().unwrap().contains_key("key") {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(&*cache.entries.lock().unwrap()["key"].body, "2");
+    }
+
+    #[tokio::test]
+    async fn test_panicking_fetch() {
+        let cache = Arc::new(ResponseCache::default());
+        let result = cache
+            .get("key".into(), TTL, || async {
+                if true {
+                    panic!("fetch failed");
+                }
+                Ok(1)
+            })
+            .await;
+        assert!(matches!(result, Err(ApiError::FetchAborted)));
+        let removed = async {
+            while cache.in_flight.lock().unwrap().contains_key("key") {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), removed)
+            .await
+            .expect("panicked fetch is still in flight");
+
+        let body = cache
+            .get("key".into(), TTL, || async { Ok(2) })
+            .await
+            .unwrap();
+        assert_eq!(&*body.body, "2");
+    }
+
+    #[tokio::test]
+    async fn test_evict_oldest() {
+        let cache = Arc::new(ResponseCache::default());
+        for i in 0..=MAX_ENTRIES {
+            cache
+                .get(format!("key{i}"), TTL, move || async move { Ok(i) })
+                .await
+                .unwrap();
+        }
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert!(!entries.contains_key("key0"));
+        assert!(entries.contains_key(&format!("key{MAX_ENTRIES}")));
+    }
+}
@@ -0,0 +1,257 @@
+use std::env::var;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("invalid value for {name}: {value}")]
+pub struct ConfigError {
+    pub name: &'static str,
+    pub value: String,
+}
+
+/// Parse an optional environment variable
+pub fn env_parse<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+    match var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError { name, value }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parse an optional comma separated environment variable, returning an empty list if it isn't set
+pub fn env_list<T: FromStr>(name: &'static str) -> Result<Vec<T>, ConfigError> {
+    let Ok(value) = var(name) else {
+        return Ok(Vec::new());
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse().map_err(|_| ConfigError {
+                name,
+                value: item.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Address to listen on, either `host:port` or `unix:/path/to/socket`
+#[derive(Debug, Clone)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddr {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(ListenAddr::Unix(path.into()));
+        }
+        s.parse().map(ListenAddr::Tcp).map_err(|_| ())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub certificate: PathBuf,
+    pub key: PathBuf,
+}
+
+/// How the http server is exposed
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub listen: ListenAddr,
+    /// Origins allowed to make cross-origin requests, `*` allows any origin
+    pub cors_origins: Vec<String>,
+    pub tls: Option<TlsConfig>,
+    pub max_body_size: usize,
+    pub request_timeout: Duration,
+}
+
+impl ServerConfig {
+    /// Load the config from `LISTEN` (falling back to `127.0.0.1:$PORT`), `CORS_ORIGINS`,
+    /// `TLS_CERT` and `TLS_KEY`, `MAX_BODY_SIZE` (in bytes) and `REQUEST_TIMEOUT` (in seconds)
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let listen = match env_parse("LISTEN")? {
+            Some(listen) => listen,
+            None => {
+                let port = env_parse("PORT")?.ok_or(ConfigError {
+                    name: "PORT",
+                    value: "either PORT or LISTEN needs to be set".into(),
+                })?;
+                ListenAddr::Tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+            }
+        };
+
+        let tls = match (var("TLS_CERT"), var("TLS_KEY")) {
+            (Ok(certificate), Ok(key)) => Some(TlsConfig {
+                certificate: credential_path(certificate),
+                key: credential_path(key),
+            }),
+            (Err(_), Err(_)) => None,
+            (Ok(_), Err(_)) => {
+                return Err(ConfigError {
+                    name: "TLS_KEY",
+                    value: "a key is required when TLS_CERT is set".into(),
+                })
+            }
+            (Err(_), Ok(_)) => {
+                return Err(ConfigError {
+                    name: "TLS_CERT",
+                    value: "a certificate is required when TLS_KEY is set".into(),
+                })
+            }
+        };
+        #[cfg(unix)]
+        if let (ListenAddr::Unix(path), Some(_)) = (&listen, &tls) {
+            return Err(ConfigError {
+                name: "LISTEN",
+                value: format!("TLS isn't supported on unix socket {}", path.display()),
+            });
+        }
+
+        Ok(ServerConfig {
+            listen,
+            cors_origins: env_list("CORS_ORIGINS")?,
+            tls,
+            max_body_size: env_parse("MAX_BODY_SIZE")?.unwrap_or(64 * 1024),
+            request_timeout: Duration::from_secs(env_parse("REQUEST_TIMEOUT")?.unwrap_or(60)),
+        })
+    }
+}
+
+/// Relative paths are loaded from the systemd credentials directory if there is one
+pub fn credential_path(path: String) -> PathBuf {
+    let path = PathBuf::from(path);
+    match var("CREDENTIALS_DIRECTORY") {
+        Ok(directory) if path.is_relative() => Path::new(&directory).join(path),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::{remove_var, set_var};
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Tests changing the environment can't run concurrently
+    static ENV: Mutex<()> = Mutex::new(());
+
+    const VARS: &[&str] = &[
+        "LISTEN",
+        "PORT",
+        "TLS_CERT",
+        "TLS_KEY",
+        "CREDENTIALS_DIRECTORY",
+        "CORS_ORIGINS",
+        "MAX_BODY_SIZE",
+        "REQUEST_TIMEOUT",
+    ];
+
+    /// Replace the config variables with `vars`
+    fn set_env(vars: &[(&str, &str)]) -> MutexGuard<'static, ()> {
+        let guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        for name in VARS {
+            remove_var(name);
+        }
+        for (name, value) in vars {
+            set_var(name, value);
+        }
+        guard
+    }
+
+    #[test]
+    fn test_port_fallback() {
+        let _env = set_env(&[("PORT", "8080")]);
+        let config = ServerConfig::from_env().unwrap();
+        assert!(
+            matches!(config.listen, ListenAddr::Tcp(addr) if addr == "127.0.0.1:8080".parse().unwrap())
+        );
+        assert!(config.tls.is_none());
+        assert!(config.cors_origins.is_empty());
+        assert_eq!(config.max_body_size, 64 * 1024);
+        assert_eq!(config.request_timeout, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_listen_tcp() {
+        let _env = set_env(&[
+            ("LISTEN", "0.0.0.0:80"),
+            ("PORT", "8080"),
+            ("CORS_ORIGINS", "https://a.example, https://b.example"),
+            ("MAX_BODY_SIZE", "1024"),
+            ("REQUEST_TIMEOUT", "5"),
+        ]);
+        let config = ServerConfig::from_env().unwrap();
+        assert!(
+            matches!(config.listen, ListenAddr::Tcp(addr) if addr == "0.0.0.0:80".parse().unwrap())
+        );
+        assert_eq!(
+            config.cors_origins,
+            ["https://a.example", "https://b.example"]
+        );
+        assert_eq!(config.max_body_size, 1024);
+        assert_eq!(config.request_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_missing_listen() {
+        let env = set_env(&[]);
+        assert_eq!(ServerConfig::from_env().unwrap_err().name, "PORT");
+        drop(env);
+        let _env = set_env(&[("LISTEN", "localhost")]);
+        assert_eq!(ServerConfig::from_env().unwrap_err().name, "LISTEN");
+    }
+
+    #[test]
+    fn test_tls() {
+        let _env = set_env(&[
+            ("PORT", "443"),
+            ("TLS_CERT", "cert.pem"),
+            ("TLS_KEY", "/etc/api/key.pem"),
+            ("CREDENTIALS_DIRECTORY", "/run/credentials/api"),
+        ]);
+        let tls = ServerConfig::from_env().unwrap().tls.unwrap();
+        assert_eq!(tls.certificate, Path::new("/run/credentials/api/cert.pem"));
+        assert_eq!(tls.key, Path::new("/etc/api/key.pem"));
+    }
+
+    #[test]
+    fn test_tls_incomplete() {
+        let env = set_env(&[("PORT", "443"), ("TLS_CERT", "cert.pem")]);
+        assert_eq!(ServerConfig::from_env().unwrap_err().name, "TLS_KEY");
+        drop(env);
+        let _env = set_env(&[("PORT", "443"), ("TLS_KEY", "key.pem")]);
+        assert_eq!(ServerConfig::from_env().unwrap_err().name, "TLS_CERT");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        let env = set_env(&[("LISTEN", "unix:/run/api/api.sock")]);
+        let config = ServerConfig::from_env().unwrap();
+        assert!(
+            matches!(&config.listen, ListenAddr::Unix(path) if path == Path::new("/run/api/api.sock"))
+        );
+        drop(env);
+
+        let _env = set_env(&[
+            ("LISTEN", "unix:/run/api/api.sock"),
+            ("TLS_CERT", "cert.pem"),
+            ("TLS_KEY", "key.pem"),
+        ]);
+        assert_eq!(ServerConfig::from_env().unwrap_err().name, "LISTEN");
+    }
+}
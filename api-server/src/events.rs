@@ -0,0 +1,352 @@
+use crate::config::{env_list, env_parse, ConfigError};
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::env::var;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::Sender;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{info, warn};
+use ugc_scraper::data::{GameMode, RosterEvent, Team, TeamDiff, TeamMatches, TeamSeasonMatch};
+use ugc_scraper::{FileTransactionStore, ScrapeError, SteamID, TransactionWatcher, UgcClient};
+
+/// Change detected by one of the background watchers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A player joined or left a team
+    Roster(RosterEvent),
+    /// A watched team changed its roster, division or name
+    Team {
+        id: u32,
+        format: GameMode,
+        diff: TeamDiff,
+    },
+    /// A watched team has a new match result
+    MatchResult {
+        team: u32,
+        format: GameMode,
+        season: u32,
+        #[serde(rename = "match")]
+        game: TeamSeasonMatch,
+    },
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Roster(_) => "roster",
+            Event::Team { .. } => "team",
+            Event::MatchResult { .. } => "match_result",
+        }
+    }
+}
+
+/// Only pass through events for a format, team or player
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub format: Option<GameMode>,
+    pub team: Option<u32>,
+    pub steam_id: Option<SteamID>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        let (format, teams, players) = match event {
+            Event::Roster(event) => (
+                event.format,
+                vec![event.transaction.team.id],
+                vec![event.transaction.steam_id],
+            ),
+            Event::Team { id, format, diff } => (
+                *format,
+                vec![*id],
+                diff.added_members
+                    .iter()
+                    .chain(diff.removed_members.iter())
+                    .map(|member| member.steam_id)
+                    .chain(diff.role_changes.iter().map(|change| change.steam_id))
+                    .collect(),
+            ),
+            Event::MatchResult {
+                team, format, game, ..
+            } => (
+                *format,
+                [Some(*team), game.result.opponents().map(|team| team.id)]
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                Vec::new(),
+            ),
+        };
+        allows(&self.format, |filter| *filter == format)
+            && allows(&self.team, |filter| teams.contains(filter))
+            && allows(&self.steam_id, |filter| players.contains(filter))
+    }
+}
+
+fn allows<T>(filter: &Option<T>, matches: impl FnOnce(&T) -> bool) -> bool {
+    match filter {
+        Some(filter) => matches(filter),
+        None => true,
+    }
+}
+
+/// Which pages to poll for changes
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    formats: Vec<GameMode>,
+    teams: Vec<u32>,
+    interval: Duration,
+    state_dir: Option<String>,
+}
+
+impl WatchConfig {
+    /// Load the config from `WATCH_FORMATS`, `WATCH_TEAMS`, `WATCH_INTERVAL` (in seconds, at least 1)
+    /// and `STATE_DIR`, watching is disabled if no formats or teams are configured
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let formats = env_list("WATCH_FORMATS")?;
+        let teams = env_list("WATCH_TEAMS")?;
+        if formats.is_empty() && teams.is_empty() {
+            return Ok(None);
+        }
+        // a zero interval would make the pollers panic
+        let interval = env_parse::<NonZeroU64>("WATCH_INTERVAL")?
+            .map(NonZeroU64::get)
+            .unwrap_or(300);
+        Ok(Some(WatchConfig {
+            formats,
+            teams,
+            interval: Duration::from_secs(interval),
+            state_dir: var("STATE_DIR").ok(),
+        }))
+    }
+}
+
+/// Start the configured watchers in the background, sending all detected changes to `events`
+pub async fn spawn_watchers(
+    client: Arc<UgcClient>,
+    config: WatchConfig,
+    events: Sender<Event>,
+) -> Result<(), ScrapeError> {
+    if !config.formats.is_empty() {
+        let mut watcher = TransactionWatcher::new(client.as_ref().clone())
+            .formats(config.formats.iter().copied())
+            .interval(config.interval);
+        if let Some(state_dir) = &config.state_dir {
+            let store = FileTransactionStore::open(format!("{state_dir}/transactions")).await?;
+            watcher = watcher.store(store);
+        }
+        let events = events.clone();
+        tokio::spawn(async move {
+            let mut stream = Box::pin(watcher.stream());
+            while let Some(event) = stream.next().await {
+                // no subscribers is not an error, there might be no webhooks configured
+                let _ = events.send(Event::Roster(event));
+            }
+        });
+        info!(formats = ?config.formats, "watching transactions");
+    }
+
+    if !config.teams.is_empty() {
+        info!(teams = ?config.teams, "watching teams");
+        tokio::spawn(watch_teams(client, config.teams, config.interval, events));
+    }
+    Ok(())
+}
+
+async fn watch_teams(
+    client: Arc<UgcClient>,
+    teams: Vec<u32>,
+    period: Duration,
+    events: Sender<Event>,
+) {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut known_teams: HashMap<u32, Team> = HashMap::new();
+    let mut known_matches: HashMap<u32, HashSet<u32>> = HashMap::new();
+
+    loop {
+        ticker.tick().await;
+        for &id in &teams {
+            match client.team(id).await {
+                Ok(team) => {
+                    if let Some(previous) = known_teams.get(&id) {
+                        let diff = TeamDiff::between(previous, &team);
+                        if !diff.is_empty() {
+                            let _ = events.send(Event::Team {
+                                id,
+                                format: team.format,
+                                diff,
+                            });
+                        }
+                    }
+                    known_teams.insert(id, team);
+                }
+                Err(error) => warn!(?error, team = id, "failed to fetch team"),
+            }
+
+            match client.team_matches(id).await {
+                Ok(matches) => {
+                    // the first poll only records the existing results
+                    let first_poll = !known_matches.contains_key(&id);
+                    let seen = known_matches.entry(id).or_default();
+                    for event in new_results(id, matches, seen) {
+                        if !first_poll {
+                            let _ = events.send(event);
+                        }
+                    }
+                }
+                Err(error) => warn!(?error, team = id, "failed to fetch team matches"),
+            }
+        }
+    }
+}
+
+fn new_results(team: u32, matches: TeamMatches, seen: &mut HashSet<u32>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for season in matches.seasons {
+        for game in season.matches {
+            let (Some(id), Some(_)) = (game.result.match_id(), game.result.outcome()) else {
+                continue;
+            };
+            if seen.insert(id) {
+                events.push(Event::MatchResult {
+                    team,
+                    format: season.format,
+                    season: season.season,
+                    game,
+                });
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+    use ugc_scraper::data::{
+        Division, MatchDate, MatchResult, Membership, MembershipRole, Side, TeamRef, Transaction,
+        TransactionAction,
+    };
+
+    fn team(id: u32) -> TeamRef {
+        TeamRef {
+            name: format!("team {id}"),
+            id,
+        }
+    }
+
+    fn roster(format: GameMode, team_id: u32, steam_id: u64) -> Event {
+        Event::Roster(RosterEvent {
+            format,
+            transaction: Transaction {
+                name: "player".into(),
+                steam_id: SteamID::from(steam_id),
+                action: TransactionAction::Joined,
+                team: team(team_id),
+                division: Division::from("Steel N.Amer"),
+                date: date!(2023 - 11 - 13),
+            },
+        })
+    }
+
+    #[test]
+    fn test_filter_roster() {
+        let event = roster(GameMode::Fours, 1, 76561198062801366);
+        assert!(EventFilter::default().matches(&event));
+        let filter = EventFilter {
+            format: Some(GameMode::Fours),
+            team: Some(1),
+            steam_id: Some(SteamID::from(76561198062801366)),
+        };
+        assert!(filter.matches(&event));
+
+        let other_format = EventFilter {
+            format: Some(GameMode::Sixes),
+            ..EventFilter::default()
+        };
+        assert!(!other_format.matches(&event));
+        let other_team = EventFilter {
+            team: Some(2),
+            ..EventFilter::default()
+        };
+        assert!(!other_team.matches(&event));
+        let other_player = EventFilter {
+            steam_id: Some(SteamID::from(76561198024494988)),
+            ..EventFilter::default()
+        };
+        assert!(!other_player.matches(&event));
+    }
+
+    #[test]
+    fn test_filter_team_members() {
+        let event = Event::Team {
+            id: 1,
+            format: GameMode::Highlander,
+            diff: TeamDiff {
+                removed_members: vec![Membership {
+                    name: "player".into(),
+                    steam_id: SteamID::from(76561198062801366),
+                    role: MembershipRole::Member,
+                    since: datetime!(2023-11-13 12:00 UTC),
+                }],
+                ..TeamDiff::default()
+            },
+        };
+        let player = EventFilter {
+            steam_id: Some(SteamID::from(76561198062801366)),
+            ..EventFilter::default()
+        };
+        assert!(player.matches(&event));
+        let other_player = EventFilter {
+            steam_id: Some(SteamID::from(76561198024494988)),
+            ..EventFilter::default()
+        };
+        assert!(!other_player.matches(&event));
+    }
+
+    #[test]
+    fn test_filter_match_opponent() {
+        let event = Event::MatchResult {
+            team: 1,
+            format: GameMode::Sixes,
+            season: 40,
+            game: TeamSeasonMatch {
+                division: Division::from("Silver Euro"),
+                week: 1,
+                date: MatchDate::new("Mon Oct 09"),
+                side: Side::Home,
+                result: MatchResult::Pending {
+                    id: 116246,
+                    opponent: team(2),
+                    score: None,
+                    score_opponent: None,
+                },
+                map: "cp_process_final".into(),
+            },
+        };
+        for id in [1, 2] {
+            let filter = EventFilter {
+                team: Some(id),
+                ..EventFilter::default()
+            };
+            assert!(filter.matches(&event));
+        }
+        let other_team = EventFilter {
+            team: Some(3),
+            ..EventFilter::default()
+        };
+        assert!(!other_team.matches(&event));
+        // match results aren't tied to a player
+        let player = EventFilter {
+            steam_id: Some(SteamID::from(76561198062801366)),
+            ..EventFilter::default()
+        };
+        assert!(!player.matches(&event));
+    }
+}
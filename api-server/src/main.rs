@@ -1,24 +1,87 @@
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+mod cache;
+mod config;
+mod events;
+mod webhook;
+
+use crate::cache::ResponseCache;
+use crate::config::{env_parse, ConfigError, ListenAddr, ServerConfig};
+use crate::events::{spawn_watchers, Event, EventFilter, WatchConfig};
+use crate::webhook::{spawn_webhooks, WebhookConfig};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::header::{ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::sse::{self, KeepAlive, Sse};
 use axum::response::{IntoResponse, Response};
-use axum::{routing::get, Json, Router};
+use axum::{routing::get, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
+use futures_util::{stream, Stream, StreamExt};
 use main_error::MainResult;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::env::var;
-use std::net::Ipv4Addr;
+use std::future::Future;
+#[cfg(unix)]
+use std::io::ErrorKind;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use steamid_ng::{SteamID, SteamIDError};
 use thiserror::Error;
 use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::signal;
-use tracing::{debug, error, instrument};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::timeout::TimeoutLayer;
+use tracing::{debug, error, info, instrument, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use ugc_scraper::data::GameMode;
-use ugc_scraper::{ScrapeError, UgcClient};
+use ugc_scraper::{CacheConfig, ScrapeError, UgcClient};
+
+const PLAYER_TTL: Duration = Duration::from_secs(10 * 60);
+const TEAMS_TTL: Duration = Duration::from_secs(30 * 60);
+const TRANSACTIONS_TTL: Duration = Duration::from_secs(2 * 60);
+const TEAM_TTL: Duration = Duration::from_secs(5 * 60);
+const MATCH_TTL: Duration = Duration::from_secs(10 * 60);
+const MAPS_TTL: Duration = Duration::from_secs(60 * 60);
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 struct AppState {
     client: Arc<UgcClient>,
+    cache: Arc<ResponseCache>,
+    events: broadcast::Sender<Event>,
+    /// Whether any watchers are configured to produce events
+    watching: bool,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl AppState {
+    /// Serve a response from the cache, using `fetch` to scrape it when needed
+    async fn cached<T, F, Fut>(
+        &self,
+        headers: &HeaderMap,
+        key: String,
+        ttl: Duration,
+        fetch: F,
+    ) -> Result<Response, ApiError>
+    where
+        T: Serialize + Send + 'static,
+        F: FnOnce(Arc<UgcClient>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, ScrapeError>> + Send + 'static,
+    {
+        let client = self.client.clone();
+        let body = self
+            .cache
+            .get(key, ttl, move || async move { Ok(fetch(client).await?) })
+            .await?;
+        Ok(body.respond(headers))
+    }
 }
 
 #[derive(Debug, Error)]
@@ -29,23 +92,39 @@ enum ApiError {
     Scrape(#[from] ScrapeError),
     #[error("malformed request")]
     Malformed(String),
+    #[error("failed to serialize response: {0:#}")]
+    Serialize(serde_json::Error),
+    #[error("no watchers are configured")]
+    NoWatchers,
+    /// Error of a fetch shared by concurrent requests
+    #[error(transparent)]
+    Shared(Arc<ApiError>),
+    #[error("fetching the response was aborted")]
+    FetchAborted,
+}
+
+impl ApiError {
+    fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
+            Self::SteamId(err) => (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)),
+            Self::Malformed(err) => (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)),
+            Self::Scrape(ScrapeError::NotFound) => (StatusCode::NOT_FOUND, String::new()),
+            Self::Scrape(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err)),
+            Self::Serialize(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err)),
+            Self::NoWatchers => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "no watchers are configured, set WATCH_FORMATS or WATCH_TEAMS".into(),
+            ),
+            Self::Shared(err) => err.status_and_message(),
+            Self::FetchAborted => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         error!(error = ?self, "error while handling request");
-        match self {
-            Self::SteamId(err) => {
-                (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)).into_response()
-            }
-            Self::Malformed(err) => {
-                (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)).into_response()
-            }
-            Self::Scrape(ScrapeError::NotFound) => (StatusCode::NOT_FOUND, "").into_response(),
-            Self::Scrape(err) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err)).into_response()
-            }
-        }
+        self.status_and_message().into_response()
     }
 }
 
@@ -58,7 +137,33 @@ async fn main() -> MainResult {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let port = var("PORT")?.parse()?;
+    let server = ServerConfig::from_env()?;
+    // responses are cached by the server, scraped pages are only kept for conditional requests
+    let mut cache = CacheConfig::new().always_revalidate();
+    if let Ok(cache_dir) = var("CACHE_DIR") {
+        cache = cache.directory(cache_dir);
+    }
+    let client = Arc::new(UgcClient::builder().cache(cache).build()?);
+
+    let stale_while_revalidate = Duration::from_secs(env_parse("CACHE_STALE")?.unwrap_or(600));
+
+    let (events, _) = broadcast::channel(256);
+    if let Some(webhooks) = WebhookConfig::from_env()? {
+        spawn_webhooks(webhooks, events.subscribe());
+    }
+    let watch_config = WatchConfig::from_env()?;
+    let watching = watch_config.is_some();
+    if let Some(config) = watch_config {
+        spawn_watchers(client.clone(), config, events.clone()).await?;
+    }
+
+    // event streams never end by themselves, so they're closed on shutdown as well
+    let (stop, shutdown) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = stop.send(true);
+    });
+
     // build our application with a route
     let app = Router::new()
         .route("/", get(handler))
@@ -71,121 +176,383 @@ async fn main() -> MainResult {
         .route("/team/{id}/matches", get(team_matches))
         .route("/match/{id}", get(match_page))
         .route("/maps/{format}", get(map_history))
-        .with_state(AppState::default());
+        .route("/events", get(events_sse))
+        .route("/events/ws", get(events_ws))
+        .with_state(AppState {
+            client,
+            cache: Arc::new(ResponseCache::new(stale_while_revalidate)),
+            events,
+            watching,
+            shutdown: shutdown.clone(),
+        })
+        .layer(DefaultBodyLimit::max(server.max_body_size))
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            server.request_timeout,
+        ))
+        .layer(cors_layer(&server.cors_origins)?);
 
-    let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), port)).await?;
-    tracing::info!("listening on http://{}", listener.local_addr().unwrap());
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .unwrap();
+    match (server.listen, server.tls) {
+        (ListenAddr::Tcp(addr), None) => {
+            let listener = TcpListener::bind(addr).await?;
+            info!("listening on http://{}", listener.local_addr()?);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(stopped(shutdown))
+                .await?;
+        }
+        (ListenAddr::Tcp(addr), Some(tls)) => {
+            let rustls = RustlsConfig::from_pem_file(tls.certificate, tls.key).await?;
+            let handle = Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    stopped(shutdown).await;
+                    handle.graceful_shutdown(Some(Duration::from_secs(10)));
+                }
+            });
+            info!("listening on https://{}", addr);
+            axum_server::bind_rustls(addr, rustls)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        #[cfg(unix)]
+        (ListenAddr::Unix(path), _) => {
+            // remove the socket left behind by a previous run, but never any other file
+            match std::fs::symlink_metadata(&path) {
+                Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(&path)?,
+                Ok(_) => {
+                    return Err(ConfigError {
+                        name: "LISTEN",
+                        value: format!("{} exists and isn't a socket", path.display()),
+                    }
+                    .into())
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+            let listener = UnixListener::bind(&path)?;
+            info!("listening on unix:{}", path.display());
+            axum::serve(listener, app)
+                .with_graceful_shutdown(stopped(shutdown))
+                .await?;
+        }
+    }
 
     Ok(())
 }
 
+fn cors_layer(origins: &[String]) -> Result<CorsLayer, ConfigError> {
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET])
+        .allow_headers([IF_NONE_MATCH])
+        .expose_headers([ETAG]);
+    if origins.iter().any(|origin| origin == "*") {
+        return Ok(cors.allow_origin(Any));
+    }
+    let origins = origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin).map_err(|_| ConfigError {
+                name: "CORS_ORIGINS",
+                value: origin.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(cors.allow_origin(AllowOrigin::list(origins)))
+}
+
 async fn handler() -> &'static str {
     include_str!("../README.md")
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn player(
     Path(id): Path<String>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let steam_id = SteamID::try_from(id.as_str())?;
     debug!(player = steam_id.steam3(), "requesting player");
-    let response = state.client.player(steam_id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("player/{}", u64::from(steam_id)),
+            PLAYER_TTL,
+            move |client| async move { client.player(steam_id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn player_history(
     Path(id): Path<String>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let steam_id = SteamID::try_from(id.as_str())?;
     debug!(player = steam_id.steam3(), "requesting player history");
-    let response = state.client.player_team_history(steam_id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("player/{}/history", u64::from(steam_id)),
+            PLAYER_TTL,
+            move |client| async move { client.player_team_history(steam_id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn teams(
     Path(format): Path<String>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let mode = match GameMode::from_str(&format) {
         Ok(mode) => mode,
         _ => return Err(ApiError::Malformed(format!("invalid game mode {}", format))),
     };
-    let response = state.client.teams(mode).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("teams/{mode}"),
+            TEAMS_TTL,
+            move |client| async move { client.teams(mode).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn transactions(
     Path(format): Path<String>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let mode = match GameMode::from_str(&format) {
         Ok(mode) => mode,
         _ => return Err(ApiError::Malformed(format!("invalid game mode {}", format))),
     };
-    let response = state.client.transactions(mode).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("transactions/{mode}"),
+            TRANSACTIONS_TTL,
+            move |client| async move { client.transactions(mode).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn team(
     Path(id): Path<u32>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     debug!(team = id, "requesting team");
-    let response = state.client.team(id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("team/{id}"),
+            TEAM_TTL,
+            move |client| async move { client.team(id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn team_roster(
     Path(id): Path<u32>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     debug!(team = id, "requesting team roster");
-    let response = state.client.team_roster_history(id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("team/{id}/roster"),
+            TEAM_TTL,
+            move |client| async move { client.team_roster_history(id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn team_matches(
     Path(id): Path<u32>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     debug!(team = id, "requesting team matches");
-    let response = state.client.team_matches(id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("team/{id}/matches"),
+            TEAM_TTL,
+            move |client| async move { client.team_matches_with_dates(id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn match_page(
     Path(id): Path<u32>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     debug!(r#match = id, "requesting match");
-    let response = state.client.match_info(id).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("match/{id}"),
+            MATCH_TTL,
+            move |client| async move { client.match_info_with_date(id).await },
+        )
+        .await
 }
 
-#[instrument(skip(state))]
+#[instrument(skip(state, headers))]
 async fn map_history(
     Path(format): Path<String>,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let mode = match GameMode::from_str(&format) {
         Ok(mode) => mode,
         _ => return Err(ApiError::Malformed(format!("invalid game mode {}", format))),
     };
-    let response = state.client.map_history(mode).await?;
-    Ok(Json(response))
+    state
+        .cached(
+            &headers,
+            format!("maps/{mode}"),
+            MAPS_TTL,
+            move |client| async move { client.map_history(mode).await },
+        )
+        .await
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    format: Option<String>,
+    team: Option<u32>,
+    steam_id: Option<String>,
+}
+
+impl TryFrom<EventsQuery> for EventFilter {
+    type Error = ApiError;
+
+    fn try_from(query: EventsQuery) -> Result<Self, Self::Error> {
+        let format = query
+            .format
+            .map(|format| {
+                GameMode::from_str(&format)
+                    .map_err(|_| ApiError::Malformed(format!("invalid game mode {}", format)))
+            })
+            .transpose()?;
+        let steam_id = query
+            .steam_id
+            .map(|id| SteamID::try_from(id.as_str()))
+            .transpose()?;
+        Ok(EventFilter {
+            format,
+            team: query.team,
+            steam_id,
+        })
+    }
+}
+
+/// Receive the next event matching the filter, or `None` once the watchers are stopped
+async fn next_event(
+    events: &mut broadcast::Receiver<Event>,
+    filter: &EventFilter,
+) -> Option<Event> {
+    loop {
+        match events.recv().await {
+            Ok(event) if filter.matches(&event) => return Some(event),
+            Ok(_) => {}
+            Err(RecvError::Lagged(skipped)) => warn!(skipped, "event subscriber fell behind"),
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[instrument(skip(state))]
+async fn events_sse(
+    Query(query): Query<EventsQuery>,
+    State(state): State<AppState>,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, ApiError> {
+    if !state.watching {
+        return Err(ApiError::NoWatchers);
+    }
+    let filter = EventFilter::try_from(query)?;
+    let events = state.events.subscribe();
+    let stream = stream::unfold((events, filter), |(mut events, filter)| async move {
+        loop {
+            let event = next_event(&mut events, &filter).await?;
+            match sse::Event::default().event(event.name()).json_data(&event) {
+                Ok(message) => return Some((Ok(message), (events, filter))),
+                Err(error) => error!(?error, "failed to serialize event"),
+            }
+        }
+    })
+    .take_until(stopped(state.shutdown));
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[instrument(skip(state, upgrade))]
+async fn events_ws(
+    upgrade: WebSocketUpgrade,
+    Query(query): Query<EventsQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    if !state.watching {
+        return Err(ApiError::NoWatchers);
+    }
+    let filter = EventFilter::try_from(query)?;
+    let events = state.events.subscribe();
+    Ok(upgrade.on_upgrade(move |socket| stream_events(socket, events, filter, state.shutdown)))
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<Event>,
+    filter: EventFilter,
+    shutdown: watch::Receiver<bool>,
+) {
+    let shutdown = stopped(shutdown);
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            event = next_event(&mut events, &filter) => {
+                let Some(event) = event else {
+                    break;
+                };
+                let message = match serde_json::to_string(&event) {
+                    Ok(message) => message,
+                    Err(error) => {
+                        error!(?error, "failed to serialize event");
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(message.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Resolves once the server is shutting down
+async fn stopped(mut shutdown: watch::Receiver<bool>) {
+    // an error means the sender is gone, which only happens once the server stopped
+    let _ = shutdown.wait_for(|stopped| *stopped).await;
 }
 
 async fn shutdown_signal() {
@@ -0,0 +1,211 @@
+use crate::config::{credential_path, env_list, env_parse, ConfigError};
+use crate::events::Event;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::Sha256;
+use std::env::var;
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver as QueueReceiver};
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+
+const SIGNATURE_HEADER: &str = "X-Ugc-Signature-256";
+const TIMESTAMP_HEADER: &str = "X-Ugc-Timestamp";
+const EVENT_HEADER: &str = "X-Ugc-Event";
+
+/// Webhooks to POST all events to
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    urls: Vec<String>,
+    secret: Option<String>,
+    max_attempts: u32,
+    queue_size: usize,
+}
+
+impl WebhookConfig {
+    /// Load the config from `WEBHOOK_URLS`, `WEBHOOK_SECRET` (or `WEBHOOK_SECRET_FILE`),
+    /// `WEBHOOK_MAX_ATTEMPTS` and `WEBHOOK_QUEUE_SIZE`, webhooks are disabled if no urls are configured
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let urls: Vec<String> = env_list("WEBHOOK_URLS")?;
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        let secret = match (var("WEBHOOK_SECRET"), var("WEBHOOK_SECRET_FILE")) {
+            (Ok(secret), _) => Some(secret),
+            (Err(_), Ok(path)) => {
+                let path = credential_path(path);
+                let secret = std::fs::read_to_string(&path).map_err(|error| ConfigError {
+                    name: "WEBHOOK_SECRET_FILE",
+                    value: format!("failed to read {}: {error}", path.display()),
+                })?;
+                Some(secret.trim().to_string())
+            }
+            (Err(_), Err(_)) => None,
+        };
+        Ok(Some(WebhookConfig {
+            urls,
+            secret: secret.filter(|secret| !secret.is_empty()),
+            max_attempts: env_parse("WEBHOOK_MAX_ATTEMPTS")?.unwrap_or(8).max(1),
+            queue_size: env_parse("WEBHOOK_QUEUE_SIZE")?.unwrap_or(1024).max(1),
+        }))
+    }
+}
+
+/// A serialized event waiting to be delivered
+#[derive(Debug, Clone)]
+struct Delivery {
+    event: &'static str,
+    body: String,
+}
+
+/// Deliver all events from `events` to the configured webhooks in the background
+///
+/// Every webhook gets its own queue, so a slow or failing endpoint doesn't hold up the others.
+pub fn spawn_webhooks(config: WebhookConfig, mut events: Receiver<Event>) {
+    let client = Client::new();
+    let queues: Vec<_> = config
+        .urls
+        .iter()
+        .map(|url| {
+            let (sender, receiver) = channel(config.queue_size);
+            tokio::spawn(deliver(
+                client.clone(),
+                url.clone(),
+                config.secret.clone(),
+                config.max_attempts,
+                receiver,
+            ));
+            (url.clone(), sender)
+        })
+        .collect();
+
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "webhooks fell behind, events were dropped");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let body = match serde_json::to_string(&event) {
+                Ok(body) => body,
+                Err(error) => {
+                    error!(?error, "failed to serialize event");
+                    continue;
+                }
+            };
+            let delivery = Delivery {
+                event: event.name(),
+                body,
+            };
+            for (url, queue) in &queues {
+                if let Err(TrySendError::Full(_)) = queue.try_send(delivery.clone()) {
+                    warn!(url, "webhook queue is full, dropping event");
+                }
+            }
+        }
+    });
+}
+
+async fn deliver(
+    client: Client,
+    url: String,
+    secret: Option<String>,
+    max_attempts: u32,
+    mut queue: QueueReceiver<Delivery>,
+) {
+    while let Some(delivery) = queue.recv().await {
+        let mut attempt = 1;
+        loop {
+            match send(&client, &url, secret.as_deref(), &delivery).await {
+                Ok(()) => {
+                    debug!(url, event = delivery.event, "delivered webhook");
+                    break;
+                }
+                Err(error) if attempt < max_attempts => {
+                    let delay = retry_delay(attempt);
+                    warn!(
+                        ?error,
+                        url,
+                        attempt,
+                        ?delay,
+                        "failed to deliver webhook, retrying"
+                    );
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) => {
+                    error!(
+                        ?error,
+                        url, attempt, "failed to deliver webhook, dropping event"
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send(
+    client: &Client,
+    url: &str,
+    secret: Option<&str>,
+    delivery: &Delivery,
+) -> Result<(), reqwest::Error> {
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header(EVENT_HEADER, delivery.event)
+        .timeout(Duration::from_secs(30))
+        .body(delivery.body.clone());
+    if let Some(secret) = secret {
+        // signed per attempt, so receivers can reject old deliveries that are replayed
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        request = request
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, sign(secret, timestamp, &delivery.body));
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Exponential backoff starting at 5 seconds, up to 10 minutes
+fn retry_delay(attempt: u32) -> Duration {
+    let delay = Duration::from_secs(5).saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    delay.min(Duration::from_secs(600))
+}
+
+/// Hex encoded HMAC-SHA256 of `<timestamp>.<body>`, formatted like `sha256=<hex>`
+fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body.as_bytes());
+    let mut signature = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(signature, "{byte:02x}");
+    }
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("secret", 1700000000, r#"{"event":"test"}"#),
+            "sha256=e6a22eb66e93669c75e7a035a110d9a2ccfa7cdef62d0ecb361671b92718ee9f"
+        );
+    }
+}
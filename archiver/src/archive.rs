@@ -5,27 +5,15 @@ use sqlx::{query, query_as, Error, Executor, PgPool, Postgres};
 use std::ops::Range;
 use std::str::FromStr;
 use thiserror::Error;
-use time::format_description::FormatItem;
 use time::macros::format_description;
-use time::parsing::Parsed;
-use time::Date;
+use time::{Date, OffsetDateTime};
 use tokio_stream::Stream;
-use tracing::{debug, error, warn};
+use tracing::warn;
 use ugc_scraper_types::{
     serde_steam_id_as_string, Class, GameMode, MapHistory, MatchInfo, Membership, MembershipRole,
     NameChange, Player, Record, Region, RosterHistory, SteamID, Team, TeamRef, TeamSeason,
 };
 
-const MATCH_DATE_FORMAT: &[FormatItem<'static>] = format_description!(
-    "[weekday case_sensitive:false repr:short], [month repr:short] [day padding:none] [year]"
-);
-const MATCH_DATE_FORMAT2: &[FormatItem<'static>] = format_description!(
-    "[weekday case_sensitive:false repr:short] [month repr:short] [day padding:none] [year]"
-);
-
-#[allow(dead_code)]
-const MATCH_DATE_FORMATS: &[&[FormatItem<'static>]] = &[MATCH_DATE_FORMAT, MATCH_DATE_FORMAT2];
-
 #[derive(Debug, Error)]
 pub enum ArchiveError {
     #[error("Invalid database {url}: {error:#}")]
@@ -63,6 +51,7 @@ impl Archive {
     }
 
     pub async fn store_match(&self, id: i32, match_info: MatchInfo) -> Result<(), ArchiveError> {
+        let comment = match_info.comments.first();
         query!(
             "INSERT INTO matches (
                 id, team_home, team_away, score_home, score_away, comment, comment_author, map, format, week
@@ -72,8 +61,8 @@ impl Archive {
             match_info.team_away.id as i32,
             match_info.score_home as i16,
             match_info.score_away as i16,
-            match_info.comment,
-            match_info.comment_author,
+            comment.map(|comment| comment.body.as_str()),
+            comment.and_then(|comment| comment.author.as_deref()),
             match_info.map,
             match_info.format as GameMode,
             match_info.week as i32,
@@ -119,7 +108,7 @@ impl Archive {
             team.region as Option<Region>,
             team.timezone,
             team.steam_group,
-            team.division,
+            team.division.to_string(),
             team.description,
         )
         .execute(&mut *transaction)
@@ -450,7 +439,7 @@ impl Archive {
                 u64::from(player.steam_id) as i64,
                 honors.team.id as i32,
                 honors.season as i16,
-                honors.division,
+                honors.division.to_string(),
                 honors.format as GameMode,
             )
             .execute(&mut *transaction)
@@ -486,8 +475,14 @@ impl Archive {
                 error,
             })?;
 
-        // who knows, the website doesn't say
-        let current_season_year = 2024;
+        // the api server resolves the years of the current season, use those for any dates it couldn't
+        let current_season_year =
+            maps.current
+                .maps
+                .iter()
+                .find_map(|map| map.date.date)
+                .map(|date| date.year())
+                .unwrap_or_else(|| OffsetDateTime::now_utc().year()) as u16;
 
         for week in maps.weeks(current_season_year) {
             let week = week.map_err(|_| ArchiveError::DateFormat { format })?;
@@ -568,28 +563,6 @@ impl Archive {
             .is_some())
     }
 
-    pub async fn get_match_year(
-        &self,
-        format: GameMode,
-        season: u32,
-        week: u8,
-    ) -> Result<Option<u32>, ArchiveError> {
-        let option = query!(
-            "SELECT date FROM maps WHERE format = $1 AND week = $2 AND season = $3",
-            format as GameMode,
-            week as i32,
-            season as i32,
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|error| ArchiveError::Query {
-            description: "searching map history",
-            error,
-        })?;
-
-        Ok(option.map(|row| row.date.year() as u32))
-    }
-
     pub async fn get_team_format(&self, id: u32) -> Result<GameMode, ArchiveError> {
         Ok(query!(
             r#"SELECT format as "format: GameMode" FROM teams WHERE id = $1"#,
@@ -690,22 +663,18 @@ impl Archive {
 
         for match_info in season.matches.iter() {
             if let Some(id) = match_info.result.match_id() {
-                let Some(year) = self
-                    .get_match_year(format, season.season, match_info.week)
-                    .await?
-                else {
-                    error!(
+                let Some(date) = match_info.date.date else {
+                    warn!(
                         r#match = id,
                         ?format,
                         season = season.season,
                         week = match_info.week,
-                        "Can't find year'"
+                        date = match_info.date.text,
+                        "match date couldn't be resolved"
                     );
-                    panic!("Can't find year for match");
+                    continue;
                 };
 
-                let date = parse_match_date(&match_info.date, year as i32);
-
                 query!(
                     "UPDATE matches SET default_date = $2 WHERE id = $1",
                     id as i32,
@@ -919,49 +888,3 @@ fn serialize_date_opt<S: Serializer>(
     let date = date.as_ref().map(|date| date.format(&format).unwrap());
     date.serialize(serializer)
 }
-
-#[allow(dead_code)]
-fn parse_match_date(date: &str, year: i32) -> Date {
-    if let Ok(date) = parse_old_match_date(date) {
-        return date;
-    }
-    try_date_formats(date, year, MATCH_DATE_FORMATS).expect("failed to parse date")
-}
-
-fn parse_old_match_date(date: &str) -> Result<Date, time::Error> {
-    const MATCH_DATE_FORMAT_OLD: &[FormatItem<'static>] = format_description!("[weekday case_sensitive:false repr:short], [month padding:none]/[day padding:none]/[year repr:last_two]");
-    let mut parsed = Parsed::new();
-    parsed.parse_items(date.as_bytes(), MATCH_DATE_FORMAT_OLD)?;
-
-    let year = parsed.year_last_two().unwrap() as i32 + 2000;
-    parsed.set_year(year);
-    Ok(Date::try_from(parsed)?)
-}
-
-#[test]
-fn test_parse_old_match_date() {
-    assert_eq!(
-        Date::from_calendar_date(2009, time::Month::May, 13).unwrap(),
-        parse_old_match_date("Wed, 5/13/09").unwrap()
-    );
-}
-
-#[allow(dead_code)]
-fn try_date_formats(date: &str, year: i32, formats: &[&[FormatItem<'static>]]) -> Option<Date> {
-    for format in formats {
-        match Date::parse(&format!("{} {}", date, year), format) {
-            Ok(match_date) => {
-                return Some(match_date);
-            }
-            Err(e) => {
-                debug!(error = ?e, year, date, "date format not matching");
-            }
-        };
-    }
-    None
-}
-
-#[test]
-fn test_parse_date() {
-    assert!(try_date_formats("Sun Oct 06", 2019, MATCH_DATE_FORMATS).is_some());
-}
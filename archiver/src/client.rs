@@ -1,6 +1,7 @@
 use reqwest::{Client, ClientBuilder, Error, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use thiserror::Error;
+use ugc_scraper::{RateLimit, RateLimiter};
 use ugc_scraper_types::{
     GameMode, MapHistory, MatchInfo, MembershipHistory, Player, RosterHistory, SteamID, Team,
     TeamMatches, TeamRosterData, Transaction,
@@ -26,6 +27,7 @@ pub enum UgcClientError {
 pub struct UgcClient {
     client: Client,
     api_url: String,
+    rate_limiter: RateLimiter,
 }
 
 #[allow(dead_code)]
@@ -35,13 +37,20 @@ impl UgcClient {
             .user_agent("UGC_ARCHIVER")
             .build()
             .expect("failed to build client");
-        Self { client, api_url }
+        // one request at a time, every api request can trigger a scrape of the website
+        let rate_limiter = RateLimiter::new(RateLimit::new(2.0).burst(1).concurrency(1));
+        Self {
+            client,
+            api_url,
+            rate_limiter,
+        }
     }
 
     async fn send_request<T: DeserializeOwned>(
         &self,
         endpoint: Endpoint,
     ) -> Result<T, UgcClientError> {
+        let _permit = self.rate_limiter.acquire(&self.api_url).await;
         let text = self
             .client
             .get(endpoint.build_url(&self.api_url))
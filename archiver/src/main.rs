@@ -14,8 +14,6 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::pin::pin;
 use std::str::FromStr;
-use std::time::Duration;
-use tokio::time::sleep;
 use tokio_stream::StreamExt;
 use tracing::{error, info, instrument, span, warn, Level};
 use ugc_scraper_types::GameMode;
@@ -96,7 +94,6 @@ async fn archive_matches(client: &UgcClient, archive: &Archive) -> MainResult {
         + 1;
     for id in next_match..=MAYBE_FIRST_MATCH {
         archive_match(client, archive, id).await.ok();
-        sleep(Duration::from_millis(500)).await;
     }
     Ok(())
 }
@@ -126,7 +123,6 @@ async fn archive_teams(client: &UgcClient, archive: &Archive) -> MainResult {
 
     for id in next_team..=range.end {
         archive_team(client, archive, id).await?;
-        sleep(Duration::from_millis(500)).await;
     }
     Ok(())
 }
@@ -172,7 +168,6 @@ async fn archive_team_roster_history(client: &UgcClient, archive: &Archive) -> M
                 panic!();
             }
         }
-        sleep(Duration::from_millis(500)).await;
     }
     Ok(())
 }
@@ -199,7 +194,6 @@ async fn fixup_teams(client: &UgcClient, archive: &Archive) -> MainResult {
                 panic!();
             }
         }
-        sleep(Duration::from_millis(500)).await;
     }
     Ok(())
 }
@@ -229,7 +223,6 @@ async fn archive_players(client: &UgcClient, archive: &Archive) -> MainResult {
                 panic!();
             }
         }
-        sleep(Duration::from_millis(500)).await;
     }
     Ok(())
 }
@@ -261,7 +254,6 @@ async fn fixup_matches(client: &UgcClient, archive: &Archive) -> MainResult {
                     if let Some(match_id) = season_match.result.match_id() {
                         if !archive.has_match(match_id).await? {
                             warn!(match_id, "match not archived yet");
-                            sleep(Duration::from_millis(500)).await;
                             if let Err(_) = archive_match(client, archive, match_id).await {
                                 let match_info = season_match
                                     .match_info(&matches.team, season.format)
@@ -278,8 +270,6 @@ async fn fixup_matches(client: &UgcClient, archive: &Archive) -> MainResult {
                     .update_match_details_from_team_matches(&matches.team, format, season)
                     .await?;
             }
-
-            sleep(Duration::from_millis(500)).await;
         }
     }
 
@@ -304,8 +294,6 @@ async fn fixup_matches(client: &UgcClient, archive: &Archive) -> MainResult {
                 .update_match_date_from_team_matches(format, season)
                 .await?;
         }
-
-        sleep(Duration::from_millis(500)).await;
     }
 
     Ok(())
@@ -5,6 +5,7 @@
 }:
 with lib; let
   cfg = config.services.ugc-api-server;
+  unixSocket = hasPrefix "unix:" cfg.listen;
 in
 {
   options.services.ugc-api-server = {
@@ -22,6 +23,110 @@ in
       description = "port to listen to";
     };
 
+    listen = mkOption {
+      type = types.str;
+      default = "127.0.0.1:${toString cfg.port}";
+      defaultText = literalExpression ''"127.0.0.1:''${toString config.services.ugc-api-server.port}"'';
+      example = "unix:/run/ugc-api-server/api.sock";
+      description = "address to listen on, either `host:port` (`[::]:10333` for ipv6) or `unix:/path/to/socket`";
+    };
+
+    corsOrigins = mkOption {
+      type = types.listOf types.str;
+      default = [ ];
+      example = [ "https://dashboard.example.com" ];
+      description = "origins allowed to make cross-origin requests, `*` allows any origin";
+    };
+
+    tls = {
+      certificate = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        description = "path to a pem encoded certificate chain to serve https with";
+      };
+
+      key = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        example = "/run/secrets/ugc-api-server.key";
+        description = "path to the pem encoded private key for the certificate, as a string to keep it out of the nix store";
+      };
+    };
+
+    maxBodySize = mkOption {
+      type = types.int;
+      default = 65536;
+      description = "maximum size of request bodies in bytes";
+    };
+
+    requestTimeout = mkOption {
+      type = types.int;
+      default = 60;
+      description = "seconds before a request is aborted";
+    };
+
+    diskCache = mkOption {
+      type = types.bool;
+      default = false;
+      description = "cache scraped pages in /var/cache/ugc-api-server";
+    };
+
+    cacheStale = mkOption {
+      type = types.ints.unsigned;
+      default = 600;
+      description = "seconds to serve expired responses for while they are refreshed";
+    };
+
+    watch = {
+      formats = mkOption {
+        type = types.listOf types.str;
+        default = [ ];
+        example = [ "9v9" "6v6" ];
+        description = "formats to watch roster transactions for";
+      };
+
+      teams = mkOption {
+        type = types.listOf types.ints.unsigned;
+        default = [ ];
+        example = [ 7861 ];
+        description = "team ids to watch for roster, division and name changes and new match results";
+      };
+
+      interval = mkOption {
+        type = types.ints.positive;
+        default = 300;
+        description = "seconds between polls";
+      };
+    };
+
+    webhook = {
+      urls = mkOption {
+        type = types.listOf types.str;
+        default = [ ];
+        example = [ "https://bot.example.com/ugc" ];
+        description = "urls to POST the watched events to";
+      };
+
+      secretFile = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        example = "/run/secrets/ugc-webhook-secret";
+        description = "path to the secret to sign deliveries with, as a string to keep it out of the nix store";
+      };
+
+      maxAttempts = mkOption {
+        type = types.ints.positive;
+        default = 8;
+        description = "attempts per event before it's dropped";
+      };
+
+      queueSize = mkOption {
+        type = types.ints.positive;
+        default = 1024;
+        description = "events queued per webhook";
+      };
+    };
+
     package = mkOption {
       type = types.package;
       description = "package to use";
@@ -29,6 +134,17 @@ in
   };
 
   config = mkIf cfg.enable {
+    assertions = [
+      {
+        assertion = (cfg.tls.certificate == null) == (cfg.tls.key == null);
+        message = "services.ugc-api-server.tls requires both a certificate and a key";
+      }
+      {
+        assertion = !(unixSocket && cfg.tls.certificate != null);
+        message = "services.ugc-api-server.tls is not supported when listening on a unix socket";
+      }
+    ];
+
     systemd.services."ugc-api-server" = {
       wantedBy = [ "multi-user.target" ];
       after = [ "network-online.target" ];
@@ -36,10 +152,39 @@ in
       environment = {
         RUST_LOG = cfg.logLevel;
         PORT = toString cfg.port;
+        LISTEN = cfg.listen;
+        CORS_ORIGINS = concatStringsSep "," cfg.corsOrigins;
+        MAX_BODY_SIZE = toString cfg.maxBodySize;
+        REQUEST_TIMEOUT = toString cfg.requestTimeout;
+        CACHE_STALE = toString cfg.cacheStale;
+        # seen transactions are remembered across restarts
+        STATE_DIR = "/var/lib/ugc-api-server";
+        WATCH_FORMATS = concatStringsSep "," cfg.watch.formats;
+        WATCH_TEAMS = concatMapStringsSep "," toString cfg.watch.teams;
+        WATCH_INTERVAL = toString cfg.watch.interval;
+        WEBHOOK_URLS = concatStringsSep "," cfg.webhook.urls;
+        WEBHOOK_MAX_ATTEMPTS = toString cfg.webhook.maxAttempts;
+        WEBHOOK_QUEUE_SIZE = toString cfg.webhook.queueSize;
+      } // optionalAttrs cfg.diskCache {
+        CACHE_DIR = "/var/cache/ugc-api-server";
+      } // optionalAttrs (cfg.tls.certificate != null) {
+        # loaded from the credentials directory, so the files don't need to be readable by the dynamic user
+        TLS_CERT = "cert.pem";
+        TLS_KEY = "key.pem";
+      } // optionalAttrs (cfg.webhook.secretFile != null) {
+        WEBHOOK_SECRET_FILE = "webhook-secret";
       };
 
       serviceConfig = {
         ExecStart = "${cfg.package}/bin/ugc-api-server";
+        RuntimeDirectory = "ugc-api-server";
+        # owned by the dynamic user and kept across restarts
+        StateDirectory = "ugc-api-server";
+        CacheDirectory = "ugc-api-server";
+        LoadCredential = optionals (cfg.tls.certificate != null) [
+          "cert.pem:${cfg.tls.certificate}"
+          "key.pem:${cfg.tls.key}"
+        ] ++ optional (cfg.webhook.secretFile != null) "webhook-secret:${cfg.webhook.secretFile}";
         Restart = "on-failure";
         DynamicUser = true;
         PrivateTmp = true;
@@ -58,7 +203,7 @@ in
         ProtectHostname = true;
         LockPersonality = true;
         ProtectKernelTunables = true;
-        RestrictAddressFamilies = "AF_INET AF_INET6";
+        RestrictAddressFamilies = "AF_INET AF_INET6" + optionalString unixSocket " AF_UNIX";
         RestrictRealtime = true;
         ProtectProc = "noaccess";
         SystemCallFilter = [ "@system-service" "~@resources" "~@privileged" ];
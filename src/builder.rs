@@ -0,0 +1,221 @@
+use crate::cache::{CacheConfig, ResponseCache};
+#[cfg(feature = "serde")]
+use crate::cassette::{Cassette, CassetteRecorder};
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::retry::RetryPolicy;
+use crate::source::{HttpSource, PageSource};
+use crate::{Result, UgcClient};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Proxy};
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.ugcleague.com";
+
+/// Builder for a [`UgcClient`] with a custom base url or http client configuration
+pub struct UgcClientBuilder {
+    base_url: String,
+    user_agent: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    client: Option<Client>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: RetryPolicy,
+    cache: Option<ResponseCache>,
+    source: Option<Arc<dyn PageSource>>,
+    #[cfg(feature = "serde")]
+    record_cassette: Option<PathBuf>,
+    #[cfg(feature = "serde")]
+    replay_cassette: Option<PathBuf>,
+}
+
+impl Default for UgcClientBuilder {
+    fn default() -> Self {
+        UgcClientBuilder {
+            base_url: DEFAULT_BASE_URL.into(),
+            user_agent: None,
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            client: None,
+            rate_limiter: Some(RateLimiter::default()),
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            source: None,
+            #[cfg(feature = "serde")]
+            record_cassette: None,
+            #[cfg(feature = "serde")]
+            replay_cassette: None,
+        }
+    }
+}
+
+impl UgcClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Base url to scrape from, defaults to `https://www.ugcleague.com`
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Timeout for the entire request, including reading the body
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use an existing http client instead of building one.
+    ///
+    /// The user agent, timeouts and proxy options are ignored when a client is provided.
+    /// Note that the scraper relies on redirects not being followed to detect missing players or teams.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Throttle requests according to the provided policy, by default 2 requests per second are allowed
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(rate_limit));
+        self
+    }
+
+    /// Share an existing rate limiter between multiple clients
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Disable all throttling of requests
+    pub fn no_rate_limit(mut self) -> Self {
+        self.rate_limiter = None;
+        self
+    }
+
+    /// Policy for retrying failed requests, by default requests are attempted up to 3 times
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Cache scraped pages, revalidating them with the server once they expire
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(ResponseCache::new(config));
+        self
+    }
+
+    /// Share an existing cache between multiple clients
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Load pages from a custom source instead of the website.
+    ///
+    /// All http related options are ignored when a custom source is used.
+    pub fn source(mut self, source: impl PageSource + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Record every fetched page into a cassette file, which can later be replayed offline
+    ///
+    /// The cassette is written when the client is dropped.
+    #[cfg(feature = "serde")]
+    pub fn record_cassette(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_cassette = Some(path.into());
+        self
+    }
+
+    /// Serve pages from a previously recorded cassette instead of the website
+    #[cfg(feature = "serde")]
+    pub fn replay_cassette(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay_cassette = Some(path.into());
+        self
+    }
+
+    pub fn build(mut self) -> Result<UgcClient> {
+        #[cfg(feature = "serde")]
+        if let Some(path) = self.replay_cassette.take() {
+            return Ok(UgcClient::with_source(Cassette::load(path)?));
+        }
+        #[cfg(feature = "serde")]
+        let record_cassette = self.record_cassette.take();
+        #[cfg(feature = "serde")]
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+
+        let source: Arc<dyn PageSource> = match self.source.take() {
+            Some(source) => source,
+            None => Arc::new(self.build_http()?),
+        };
+
+        #[cfg(feature = "serde")]
+        if let Some(path) = record_cassette {
+            let recorder = CassetteRecorder::new(source, base_url, path)?;
+            return Ok(UgcClient::with_source(recorder));
+        }
+        Ok(UgcClient::from_source(source))
+    }
+
+    /// Build the http source for scraping the live website, for use with a wrapping source
+    pub fn build_http(self) -> Result<HttpSource> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder().redirect(redirect_policy());
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build()?
+            }
+        };
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        Ok(HttpSource::new(
+            client,
+            base_url,
+            self.rate_limiter,
+            self.retry_policy,
+            self.cache,
+        ))
+    }
+}
+
+fn redirect_policy() -> Policy {
+    Policy::custom(|attempt| {
+        // the different matchpage_* redirect to each other if you use the match id from a different game mode
+        if attempt.url().path().contains("matchpage_") {
+            attempt.follow()
+        } else {
+            attempt.stop()
+        }
+    })
+}
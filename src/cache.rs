@@ -0,0 +1,360 @@
+use crate::page::PageKind;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs::{create_dir_all, read_dir};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::{read_to_string, remove_file, write};
+use tracing::warn;
+
+/// Configuration for caching scraped pages
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    capacity: usize,
+    disk_capacity: usize,
+    directory: Option<PathBuf>,
+    default_ttl: Duration,
+    ttls: HashMap<PageKind, Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            capacity: 256,
+            disk_capacity: 4096,
+            directory: None,
+            default_ttl: Duration::from_secs(5 * 60),
+            ttls: HashMap::from([
+                (PageKind::Seasons, Duration::from_secs(60 * 60)),
+                (PageKind::TeamLookup, Duration::from_secs(60 * 60)),
+                (PageKind::MapHistory, Duration::from_secs(60 * 60)),
+                (PageKind::Match, Duration::from_secs(10 * 60)),
+                (PageKind::Transactions, Duration::from_secs(60)),
+            ]),
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pages to keep in memory
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Number of pages to keep in the cache directory
+    pub fn disk_capacity(mut self, capacity: usize) -> Self {
+        self.disk_capacity = capacity.max(1);
+        self
+    }
+
+    /// Also store cached pages in a directory, so they survive restarts
+    pub fn directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Time a page is used without revalidating, for pages without a specific ttl
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Time pages of a specific kind are used without revalidating
+    pub fn ttl(mut self, kind: PageKind, ttl: Duration) -> Self {
+        self.ttls.insert(kind, ttl);
+        self
+    }
+
+    /// Revalidate every cached page before using it
+    ///
+    /// Pages are still stored to make conditional requests, for when responses are already
+    /// cached at a higher level.
+    pub fn always_revalidate(mut self) -> Self {
+        self.default_ttl = Duration::ZERO;
+        self.ttls.clear();
+        self
+    }
+
+    pub fn ttl_for(&self, kind: PageKind) -> Duration {
+        self.ttls.get(&kind).copied().unwrap_or(self.default_ttl)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CachedPage {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched: SystemTime,
+}
+
+impl CachedPage {
+    fn serialize(&self) -> String {
+        let fetched = self
+            .fetched
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        format!(
+            "etag: {}\nlast-modified: {}\nfetched: {}\n\n{}",
+            self.etag.as_deref().unwrap_or_default(),
+            self.last_modified.as_deref().unwrap_or_default(),
+            fetched,
+            self.body
+        )
+    }
+
+    fn deserialize(data: &str) -> Option<Self> {
+        let (headers, body) = data.split_once("\n\n")?;
+        let mut etag = None;
+        let mut last_modified = None;
+        let mut fetched = None;
+        for line in headers.lines() {
+            let (name, value) = line.split_once(':')?;
+            let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+            match name {
+                "etag" => etag = value,
+                "last-modified" => last_modified = value,
+                "fetched" => fetched = value.and_then(|value| value.parse().ok()),
+                _ => {}
+            }
+        }
+        Some(CachedPage {
+            body: body.to_string(),
+            etag,
+            last_modified,
+            fetched: UNIX_EPOCH + Duration::from_secs(fetched?),
+        })
+    }
+}
+
+/// In memory LRU cache of scraped pages with an optional on-disk store.
+///
+/// Clones share the same cache.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    inner: Arc<ResponseCacheInner>,
+}
+
+#[derive(Debug)]
+struct ResponseCacheInner {
+    config: CacheConfig,
+    memory: Mutex<Lru<CachedPage>>,
+    /// Names of the files in the cache directory
+    disk: Mutex<Lru<()>>,
+}
+
+#[derive(Debug)]
+struct Lru<T> {
+    tick: u64,
+    entries: HashMap<String, (u64, T)>,
+}
+
+impl<T> Default for Lru<T> {
+    fn default() -> Self {
+        Lru {
+            tick: 0,
+            entries: HashMap::default(),
+        }
+    }
+}
+
+impl<T> Lru<T> {
+    fn get(&mut self, key: &str) -> Option<&T> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (last_used, value) = self.entries.get_mut(key)?;
+        *last_used = tick;
+        Some(value)
+    }
+
+    /// Insert an entry, returning the key of the least recently used entry if it had to be evicted
+    fn insert(&mut self, key: &str, value: T, capacity: usize) -> Option<String> {
+        self.tick += 1;
+        let tick = self.tick;
+        let mut evicted = None;
+        if self.entries.len() >= capacity && !self.entries.contains_key(key) {
+            evicted = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(evicted) = &evicted {
+                self.entries.remove(evicted);
+            }
+        }
+        self.entries.insert(key.to_string(), (tick, value));
+        evicted
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(CacheConfig::default())
+    }
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        let mut disk = Lru::default();
+        if let Some(directory) = &config.directory {
+            if let Err(error) = create_dir_all(directory) {
+                warn!(directory = %directory.display(), %error, "failed to create cache directory");
+            }
+            // the directory might have been filled with a larger capacity, drop the oldest pages
+            let files = existing_files(directory);
+            let excess = files.len().saturating_sub(config.disk_capacity);
+            for name in &files[..excess] {
+                let path = directory.join(name);
+                if let Err(error) = std::fs::remove_file(&path) {
+                    warn!(path = %path.display(), %error, "failed to remove page from cache");
+                }
+            }
+            for name in &files[excess..] {
+                disk.insert(name, (), usize::MAX);
+            }
+        }
+        ResponseCache {
+            inner: Arc::new(ResponseCacheInner {
+                config,
+                memory: Mutex::default(),
+                disk: Mutex::new(disk),
+            }),
+        }
+    }
+
+    /// Remove all pages from the in-memory cache
+    pub fn clear(&self) {
+        self.inner.memory.lock().unwrap().entries.clear();
+    }
+
+    pub(crate) async fn get(&self, url: &str) -> Option<CachedPage> {
+        if let Some(page) = self.inner.memory.lock().unwrap().get(url) {
+            return Some(page.clone());
+        }
+
+        let directory = self.inner.config.directory.as_ref()?;
+        let name = file_name(url);
+        self.inner.disk.lock().unwrap().get(&name)?;
+        let page = CachedPage::deserialize(&read_to_string(directory.join(name)).await.ok()?)?;
+        self.insert_memory(url, page.clone());
+        Some(page)
+    }
+
+    pub(crate) fn is_fresh(&self, page: &CachedPage, kind: PageKind) -> bool {
+        page.fetched
+            .elapsed()
+            .map(|age| age < self.inner.config.ttl_for(kind))
+            .unwrap_or(false)
+    }
+
+    pub(crate) async fn insert(&self, url: &str, page: CachedPage) {
+        if let Some(directory) = &self.inner.config.directory {
+            let name = file_name(url);
+            let path = directory.join(&name);
+            match write(&path, page.serialize()).await {
+                Ok(()) => {
+                    let evicted = self.inner.disk.lock().unwrap().insert(
+                        &name,
+                        (),
+                        self.inner.config.disk_capacity,
+                    );
+                    if let Some(evicted) = evicted {
+                        let path = directory.join(evicted);
+                        match remove_file(&path).await {
+                            Err(error) if error.kind() != ErrorKind::NotFound => {
+                                warn!(path = %path.display(), %error, "failed to remove page from cache");
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Err(error) => {
+                    warn!(path = %path.display(), %error, "failed to write page to cache");
+                }
+            }
+        }
+        self.insert_memory(url, page);
+    }
+
+    fn insert_memory(&self, url: &str, page: CachedPage) {
+        self.inner
+            .memory
+            .lock()
+            .unwrap()
+            .insert(url, page, self.inner.config.capacity);
+    }
+}
+
+/// Hex encoded SHA-256 of the full url, so every page gets a unique file name of a fixed length
+fn file_name(url: &str) -> String {
+    let mut name = String::with_capacity(64);
+    for byte in Sha256::digest(url.as_bytes()) {
+        let _ = write!(name, "{byte:02x}");
+    }
+    name
+}
+
+/// Names of the pages already in the cache directory, least recently modified first
+fn existing_files(directory: &Path) -> Vec<String> {
+    let Ok(entries) = read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.file_name().into_string().ok()?))
+        })
+        .collect();
+    files.sort();
+    files.into_iter().map(|(_, name)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preload_trims_to_disk_capacity() {
+        let directory = std::env::temp_dir().join(format!("ugc-cache-test-{}", fastrand::u64(..)));
+        create_dir_all(&directory).unwrap();
+        for i in 0..5 {
+            std::fs::write(directory.join(format!("page{i}")), "").unwrap();
+            // give each page a distinct modification time
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let cache = ResponseCache::new(
+            CacheConfig::default()
+                .directory(directory.clone())
+                .disk_capacity(2),
+        );
+        assert_eq!(cache.inner.disk.lock().unwrap().entries.len(), 2);
+        let mut remaining: Vec<_> = existing_files(&directory);
+        remaining.sort();
+        assert_eq!(remaining, ["page3", "page4"]);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_file_name_unique() {
+        let plus = file_name("https://www.ugcleague.com/team_page.cfm?clan_id=a+b");
+        let underscore = file_name("https://www.ugcleague.com/team_page.cfm?clan_id=a_b");
+        assert_ne!(plus, underscore);
+        assert_eq!(plus.len(), 64);
+        assert_eq!(
+            file_name(&format!("https://{}", "a".repeat(1000))).len(),
+            64
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::page::Page;
+use crate::source::{PageResponse, PageSource};
+use crate::{Result, ScrapeError};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+/// A recorded scraping session that can be replayed offline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// A single recorded page fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub url: String,
+    pub status: u16,
+    #[serde(with = "time::serde::rfc3339")]
+    pub recorded_at: OffsetDateTime,
+    pub body: String,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Find the latest recorded fetch for a page
+    pub fn find(&self, page: &Page) -> Option<&Interaction> {
+        let path = page.path();
+        self.interactions
+            .iter()
+            .rev()
+            .find(|interaction| interaction.url.ends_with(&path))
+    }
+}
+
+/// Replay the recorded pages
+#[async_trait]
+impl PageSource for Cassette {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        self.fetch_response(page).await?.into_result()
+    }
+
+    async fn fetch_response(&self, page: &Page) -> Result<PageResponse> {
+        let interaction = self.find(page).ok_or(ScrapeError::NotFound)?;
+        Ok(PageResponse {
+            status: StatusCode::from_u16(interaction.status)
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            body: interaction.body.clone(),
+        })
+    }
+}
+
+/// Record every page fetched from the inner source into a cassette file
+///
+/// Recorded pages are kept in memory and written to the cassette file by [`finish`](Self::finish),
+/// or when the recorder is dropped.
+pub struct CassetteRecorder<S> {
+    inner: S,
+    base_url: String,
+    path: PathBuf,
+    recording: Mutex<Recording>,
+}
+
+struct Recording {
+    cassette: Cassette,
+    saved: usize,
+}
+
+impl<S: PageSource> CassetteRecorder<S> {
+    /// Record into the cassette at `path`, appending to it if it already exists
+    pub fn new(inner: S, base_url: impl Into<String>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let cassette = if path.exists() {
+            Cassette::load(&path)?
+        } else {
+            Cassette::default()
+        };
+        Ok(CassetteRecorder {
+            inner,
+            base_url: base_url.into(),
+            path,
+            recording: Mutex::new(Recording {
+                saved: cassette.interactions.len(),
+                cassette,
+            }),
+        })
+    }
+
+    fn record(&self, page: &Page, response: &PageResponse) {
+        let url = format!("{}{}", self.base_url, page.path());
+        debug!(url, status = response.status.as_u16(), "recording page");
+        self.recording
+            .lock()
+            .unwrap()
+            .cassette
+            .interactions
+            .push(Interaction {
+                url,
+                status: response.status.as_u16(),
+                recorded_at: OffsetDateTime::now_utc(),
+                body: response.body.clone(),
+            });
+    }
+}
+
+impl<S> CassetteRecorder<S> {
+    /// Write the pages recorded so far to the cassette file
+    pub fn finish(&self) -> Result<()> {
+        let mut recording = self.recording.lock().unwrap();
+        if recording.saved < recording.cassette.interactions.len() {
+            recording.cassette.save(&self.path)?;
+            recording.saved = recording.cassette.interactions.len();
+        }
+        Ok(())
+    }
+}
+
+impl<S> Drop for CassetteRecorder<S> {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            warn!(path = %self.path.display(), %error, "failed to save cassette");
+        }
+    }
+}
+
+#[async_trait]
+impl<S: PageSource> PageSource for CassetteRecorder<S> {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        self.fetch_response(page).await?.into_result()
+    }
+
+    async fn fetch_response(&self, page: &Page) -> Result<PageResponse> {
+        let response = self.inner.fetch_response(page).await?;
+        self.record(page, &response);
+        Ok(response)
+    }
+}
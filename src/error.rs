@@ -1,3 +1,5 @@
+use reqwest::StatusCode;
+use std::time::Duration;
 use thiserror::Error;
 use ugc_scraper_types::MallFormedTransaction;
 
@@ -7,8 +9,18 @@ pub enum ScrapeError {
     Request(#[from] reqwest::Error),
     #[error(transparent)]
     Parse(#[from] ParseError),
+    #[error("Failed to read page: {0:#}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error("Invalid cassette: {0:#}")]
+    Cassette(#[from] serde_json::Error),
     #[error("Player or team doesn't exist")]
     NotFound,
+    #[error("Unexpected response status {status}")]
+    Status {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
 }
 
 #[derive(Debug, Error, Clone)]
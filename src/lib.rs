@@ -1,7 +1,16 @@
+mod builder;
+mod cache;
+#[cfg(feature = "serde")]
+mod cassette;
 pub mod data;
 mod error;
+mod page;
 #[doc(hidden)]
 pub mod parser;
+mod rate_limit;
+mod retry;
+mod source;
+mod watcher;
 
 use crate::data::{
     GameMode, MapHistory, MatchInfo, MembershipHistory, Player, Seasons, Team, TeamRef,
@@ -11,19 +20,28 @@ use crate::parser::{
     MapHistoryParser, MatchPageParser, Parser, PlayerDetailsParser, PlayerParser, SeasonsParser,
     TeamLookupParser, TeamMatchesParser, TeamParser, TeamRosterHistoryParser, TransactionParser,
 };
+pub use builder::{UgcClientBuilder, DEFAULT_BASE_URL};
+pub use cache::{CacheConfig, ResponseCache};
+#[cfg(feature = "serde")]
+pub use cassette::{Cassette, CassetteRecorder, Interaction};
 pub use error::*;
-use reqwest::redirect::Policy;
-use reqwest::{Client, IntoUrl, Response, StatusCode};
-use std::time::Duration;
+pub use page::{Page, PageKind};
+pub use rate_limit::{RateLimit, RateLimitPermit, RateLimiter};
+pub use retry::RetryPolicy;
+pub use source::{DirectorySource, HttpSource, PageResponse, PageSource, RecordingSource};
+use std::sync::Arc;
 pub use steamid_ng::SteamID;
-use tokio::time::sleep;
-use tracing::warn;
+use time::OffsetDateTime;
 use ugc_scraper_types::TeamMatches;
+pub use watcher::{
+    FileTransactionStore, MemoryTransactionStore, TransactionStore, TransactionWatcher,
+};
 
 pub type Result<T, E = ScrapeError> = std::result::Result<T, E>;
 
+#[derive(Clone)]
 pub struct UgcClient {
-    client: Client,
+    source: Arc<dyn PageSource>,
     player_parser: PlayerParser,
     player_detail_parser: PlayerDetailsParser,
     team_parser: TeamParser,
@@ -45,16 +63,22 @@ impl Default for UgcClient {
 /// "API client" for ugc by scraping the website
 impl UgcClient {
     pub fn new() -> Self {
-        let redirect_policy = Policy::custom(|attempt| {
-            // the different matchpage_* redirect to each other if you use the match id from a different game mode
-            if attempt.url().path().contains("matchpage_") {
-                attempt.follow()
-            } else {
-                attempt.stop()
-            }
-        });
+        Self::builder().build().unwrap()
+    }
+
+    /// Configure a client with a custom base url, http client or timeouts
+    pub fn builder() -> UgcClientBuilder {
+        UgcClientBuilder::new()
+    }
+
+    /// Create a client that loads pages from the provided source
+    pub fn with_source(source: impl PageSource + 'static) -> Self {
+        Self::from_source(Arc::new(source))
+    }
+
+    pub(crate) fn from_source(source: Arc<dyn PageSource>) -> Self {
         UgcClient {
-            client: Client::builder().redirect(redirect_policy).build().unwrap(),
+            source,
             player_parser: PlayerParser::new(),
             player_detail_parser: PlayerDetailsParser::new(),
             team_parser: TeamParser::new(),
@@ -67,141 +91,114 @@ impl UgcClient {
             map_history_parser: MapHistoryParser::new(),
         }
     }
-    async fn request<U: IntoUrl>(&self, url: U) -> Result<String> {
-        let url = url.into_url()?;
-        match self.try_request(url.clone()).await {
-            Ok(res) => Ok(res),
-            Err(ScrapeError::Request(e)) => {
-                warn!(url = url.as_str(), error = ?e, "failed to send request, retrying");
-                sleep(Duration::from_secs_f32(0.5)).await;
-                self.try_request(url).await
-            }
-            Err(e) => Err(e),
-        }
-    }
-
-    async fn try_request<U: IntoUrl>(&self, url: U) -> Result<String> {
-        Ok(self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .check_not_found()?
-            .error_for_status()?
-            .text()
-            .await?)
-    }
 
     /// Retrieve player information
     pub async fn player(&self, steam_id: SteamID) -> Result<Player> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/players_page.cfm?player_id={}",
-                u64::from(steam_id)
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::Player(steam_id)).await?;
         self.player_parser.parse(&body)
     }
 
     /// Retrieve team membership history for a player
     pub async fn player_team_history(&self, steam_id: SteamID) -> Result<Vec<MembershipHistory>> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/players_page_details.cfm?player_id={}",
-                u64::from(steam_id)
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::PlayerDetails(steam_id)).await?;
         self.player_detail_parser.parse(&body)
     }
 
     /// Retrieve team information
     pub async fn team(&self, id: u32) -> Result<Team> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/team_page.cfm?clan_id={}",
-                id
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::Team(id)).await?;
         self.team_parser.parse(&body)
     }
 
     /// Retrieve team roster history
     pub async fn team_roster_history(&self, id: u32) -> Result<TeamRosterData> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/team_page_rosterhistory.cfm?clan_id={}",
-                id
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::TeamRosterHistory(id)).await?;
         self.team_roster_history_parser.parse(&body)
     }
 
     /// Retrieve team match history
+    ///
+    /// The website leaves the year out of most match dates,
+    /// use [`team_matches_with_dates`](Self::team_matches_with_dates) to get them with the year filled in.
     pub async fn team_matches(&self, id: u32) -> Result<TeamMatches> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/team_page_matches.cfm?clan_id={}",
-                id
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::TeamMatches(id)).await?;
         self.team_matches_parser.parse(&body)
     }
 
+    /// Retrieve team match history with the match dates resolved
+    pub async fn team_matches_with_dates(&self, id: u32) -> Result<TeamMatches> {
+        let mut matches = self.team_matches(id).await?;
+        self.resolve_team_match_dates(&mut matches).await?;
+        Ok(matches)
+    }
+
+    /// Resolve the year of team match dates using the map history of the formats the team played in
+    ///
+    /// This fetches the map history once for every format in the match history.
+    pub async fn resolve_team_match_dates(&self, matches: &mut TeamMatches) -> Result<()> {
+        let mut histories: Vec<(GameMode, MapHistory)> = Vec::new();
+        for season in matches.seasons.iter_mut() {
+            let history = match histories
+                .iter()
+                .position(|(format, _)| *format == season.format)
+            {
+                Some(index) => &histories[index].1,
+                None => {
+                    let history = self.map_history(season.format).await?;
+                    histories.push((season.format, history));
+                    &histories[histories.len() - 1].1
+                }
+            };
+            let season_number = u8::try_from(season.season).ok();
+            for game in season.matches.iter_mut() {
+                history.resolve_date(season_number, game.week, &mut game.date);
+            }
+        }
+        Ok(())
+    }
+
     /// Get all historical seasons by game mode
     pub async fn previous_seasons(&self) -> Result<Vec<Seasons>> {
-        let body = self.request("https://www.ugcleague.com").await?;
+        let body = self.source.fetch(&Page::Seasons).await?;
         self.seasons_parser.parse(&body)
     }
 
     pub async fn teams(&self, format: GameMode) -> Result<Vec<TeamRef>> {
-        let link = format!(
-            "https://www.ugcleague.com/team_lookup_tf2{}.cfm",
-            format.letter()
-        );
-        let body = self.request(link).await?;
+        let body = self.source.fetch(&Page::TeamLookup(format)).await?;
         self.team_lookup_parser.parse(&body)
     }
 
     /// Get match page info
+    ///
+    /// Use [`match_info_with_date`](Self::match_info_with_date) to get it with the year of the default date filled in.
     pub async fn match_info(&self, id: u32) -> Result<MatchInfo> {
-        let body = self
-            .request(format!(
-                "https://www.ugcleague.com/matchpage_tf2h.cfm?mid={}",
-                id
-            ))
-            .await?;
+        let body = self.source.fetch(&Page::Match(id)).await?;
         self.match_page_parser.parse(&body)
     }
 
-    pub async fn transactions(&self, format: GameMode) -> Result<Vec<Transaction>> {
-        let link = format!(
-            "https://www.ugcleague.com/rostertransactions_tf2{}_all.cfm",
-            format.letter()
-        );
-        let body = self.request(link).await?;
-        self.transaction_parser.parse(&body)
+    /// Get match page info with the default date resolved
+    pub async fn match_info_with_date(&self, id: u32) -> Result<MatchInfo> {
+        let mut info = self.match_info(id).await?;
+        self.resolve_match_date(&mut info).await?;
+        Ok(info)
     }
 
-    pub async fn map_history(&self, format: GameMode) -> Result<MapHistory> {
-        let link = format!(
-            "https://www.ugcleague.com/maplist_tf2{}.cfm",
-            format.letter()
-        );
-        let body = self.request(link).await?;
-        self.map_history_parser.parse(&body)
+    /// Resolve the year of the default match date using the map history of the match format
+    pub async fn resolve_match_date(&self, info: &mut MatchInfo) -> Result<()> {
+        let history = self.map_history(info.format).await?;
+        history.resolve_date(None, info.week, &mut info.default_date);
+        Ok(())
     }
-}
 
-trait ResponseExt: Sized {
-    fn check_not_found(self) -> Result<Self, ScrapeError>;
-}
+    pub async fn transactions(&self, format: GameMode) -> Result<Vec<Transaction>> {
+        let body = self.source.fetch(&Page::Transactions(format)).await?;
+        self.transaction_parser.parse(&body)
+    }
 
-impl ResponseExt for Response {
-    fn check_not_found(self) -> Result<Self, ScrapeError> {
-        if self.status() == StatusCode::FOUND {
-            Err(ScrapeError::NotFound)
-        } else {
-            Ok(self)
-        }
+    pub async fn map_history(&self, format: GameMode) -> Result<MapHistory> {
+        let body = self.source.fetch(&Page::MapHistory(format)).await?;
+        let mut history = self.map_history_parser.parse(&body)?;
+        history.resolve_current_dates(OffsetDateTime::now_utc().date());
+        Ok(history)
     }
 }
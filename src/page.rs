@@ -0,0 +1,74 @@
+use crate::data::GameMode;
+use steamid_ng::SteamID;
+
+/// The different kinds of pages scraped from the ugc website
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageKind {
+    Player,
+    PlayerDetails,
+    Team,
+    TeamRosterHistory,
+    TeamMatches,
+    Seasons,
+    TeamLookup,
+    Match,
+    Transactions,
+    MapHistory,
+}
+
+/// A single page on the ugc website
+#[derive(Debug, Clone, Copy)]
+pub enum Page {
+    Player(SteamID),
+    PlayerDetails(SteamID),
+    Team(u32),
+    TeamRosterHistory(u32),
+    TeamMatches(u32),
+    Seasons,
+    TeamLookup(GameMode),
+    Match(u32),
+    Transactions(GameMode),
+    MapHistory(GameMode),
+}
+
+impl Page {
+    pub fn kind(&self) -> PageKind {
+        match self {
+            Page::Player(_) => PageKind::Player,
+            Page::PlayerDetails(_) => PageKind::PlayerDetails,
+            Page::Team(_) => PageKind::Team,
+            Page::TeamRosterHistory(_) => PageKind::TeamRosterHistory,
+            Page::TeamMatches(_) => PageKind::TeamMatches,
+            Page::Seasons => PageKind::Seasons,
+            Page::TeamLookup(_) => PageKind::TeamLookup,
+            Page::Match(_) => PageKind::Match,
+            Page::Transactions(..) => PageKind::Transactions,
+            Page::MapHistory(_) => PageKind::MapHistory,
+        }
+    }
+
+    /// Path of the page relative to the base url
+    pub fn path(&self) -> String {
+        match self {
+            Page::Player(steam_id) => {
+                format!("/players_page.cfm?player_id={}", u64::from(*steam_id))
+            }
+            Page::PlayerDetails(steam_id) => format!(
+                "/players_page_details.cfm?player_id={}",
+                u64::from(*steam_id)
+            ),
+            Page::Team(id) => format!("/team_page.cfm?clan_id={}", id),
+            Page::TeamRosterHistory(id) => {
+                format!("/team_page_rosterhistory.cfm?clan_id={}", id)
+            }
+            Page::TeamMatches(id) => format!("/team_page_matches.cfm?clan_id={}", id),
+            Page::Seasons => "/".into(),
+            Page::TeamLookup(format) => format!("/team_lookup_tf2{}.cfm", format.letter()),
+            Page::Match(id) => format!("/matchpage_tf2h.cfm?mid={}", id),
+            Page::Transactions(format) => {
+                format!("/rostertransactions_tf2{}_all.cfm", format.letter())
+            }
+            Page::MapHistory(format) => format!("/maplist_tf2{}.cfm", format.letter()),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::Parser;
 use crate::data::{
-    CurrentSeasonMap, CurrentSeasonMapList, MapHistory, PreviousSeasonMap, PreviousSeasonMapList,
+    CurrentSeasonMap, CurrentSeasonMapList, MapHistory, MatchDate, PreviousSeasonMap,
+    PreviousSeasonMapList, SeasonId,
 };
 use crate::parser::{select_text, ElementExt};
 use crate::{ParseError, Result};
@@ -20,6 +21,7 @@ const SELECTOR_PREVIOUS_WEEK: &str = "td:nth-child(1)";
 const SELECTOR_PREVIOUS_DATE: &str = "td:nth-child(2)";
 const SELECTOR_PREVIOUS_MAP: &str = "td:nth-child(3)";
 
+#[derive(Clone)]
 pub struct MapHistoryParser {
     selector_current_row: Selector,
     selector_current_season: Selector,
@@ -64,14 +66,13 @@ impl Parser for MapHistoryParser {
     fn parse(&self, document: &str) -> Result<Self::Output> {
         let document = Html::parse_document(document);
 
-        let season = select_text(document.root_element(), &self.selector_current_season)
-            .ok_or(ParseError::ElementNotFound {
+        let season = select_text(document.root_element(), &self.selector_current_season).ok_or(
+            ParseError::ElementNotFound {
                 selector: SELECTOR_CURRENT_SEASON,
                 role: "current season number",
-            })?
-            .trim_start_matches("Season")
-            .trim();
-        let season: u8 = season.parse().map_err(|_| ParseError::InvalidText {
+            },
+        )?;
+        let season = season_number(season).ok_or(ParseError::InvalidText {
             role: "current season number",
             text: season.to_string(),
         })?;
@@ -111,8 +112,8 @@ impl Parser for MapHistoryParser {
                 Ok(CurrentSeasonMap {
                     week,
                     map,
-                    date,
-                    na_date: alt_date,
+                    date: MatchDate::new(date),
+                    na_date: alt_date.map(MatchDate::new),
                 })
             })
             .collect::<Result<_>>()?;
@@ -124,11 +125,8 @@ impl Parser for MapHistoryParser {
                 if let Some(season) = prev_season.take() {
                     previous.push(season);
                 }
-                let season = row
-                    .first_text()
-                    .unwrap_or_default()
-                    .trim_start_matches("Season ");
-                let season = season.parse().map_err(|_| ParseError::InvalidText {
+                let season = row.first_text().unwrap_or_default();
+                let season = season_number(season).ok_or(ParseError::InvalidText {
                     role: "previous season number",
                     text: season.to_string(),
                 })?;
@@ -196,3 +194,7 @@ fn parse_date(date: &str) -> Result<Date> {
     let year = 2000 + year;
     Ok(Date::from_calendar_date(year, month, date).map_err(|_| err())?)
 }
+
+fn season_number(header: &str) -> Option<u8> {
+    SeasonId::number_from_header(header)?.try_into().ok()
+}
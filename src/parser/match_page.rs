@@ -1,12 +1,15 @@
 use super::Parser;
-use crate::data::{GameMode, MatchInfo, TeamRef};
-use crate::parser::{select_last_text, select_text, team_id_from_link, ElementExt};
+use crate::data::{
+    ExternalLink, ExternalLinkKind, Forfeit, GameMode, MatchComment, MatchDate, MatchInfo,
+    RoundScore, TeamRef,
+};
+use crate::parser::{select_text, team_id_from_link, ElementExt};
 use crate::{ParseError, Result};
-use scraper::{Html, Selector};
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::sync::OnceLock;
 
 const SELECTOR_MATCH_FORMAT: &str = "h3.page-header > strong.styleColor";
-const SELECTOR_MATCH_COMMENT_AUTHOR: &str = ".row-fluid .col-md-12 span.text-success";
-const SELECTOR_MATCH_COMMENT: &str = ".row-fluid .col-md-12 > .text-center > p";
 const SELECTOR_MATCH_TEAM_LINK: &str = "a[href^=\"team_page\"]:not(.btn-large)";
 const SELECTOR_MATCH_RESULT_TEAM: &str =
     ".table.table-condensed.table-bordered tr:nth-child(2) td:nth-child(1)";
@@ -15,17 +18,99 @@ const SELECTOR_MATCH_RESULT_SCORE: &str =
 const SELECTOR_MATCH_MAP: &str = "h4.text-success.text-center > b";
 const SELECTOR_MATCH_WEEK: &str = "p.muted.text-center.nomargin > small > b:nth-child(1)";
 const SELECTOR_MATCH_DATE: &str = "p.muted.text-center.nomargin > small > b:nth-child(2)";
+const SELECTOR_MATCH_RESULT: &str = ".white-row-light-small";
+const SELECTOR_MATCH_RESULT_WINNER: &str = "h4 b";
+const SELECTOR_MATCH_RESULT_SCORES: &str = "div.text-center";
+const SELECTOR_MATCH_RESULT_HEADER: &str = "h3, h4, div.text-center";
+const SELECTOR_MATCH_COMMENTS: &str =
+    ".white-row-light-small > p, .row-fluid .col-md-12 > .text-center > p";
+const SELECTOR_MATCH_COMMENTS_AUTHOR: &str = "span b";
+const SELECTOR_MATCH_CONTENT_LINK: &str = ".container .col-md-9 a[href]";
 
+static ROUND_REGEX: OnceLock<Regex> = OnceLock::new();
+fn round_regex() -> &'static Regex {
+    ROUND_REGEX.get_or_init(|| Regex::new(r"\(\s*(\d+)\s*-\s*(\d+)\s*\)").unwrap())
+}
+
+/// Hosts that stv demos are uploaded to, including their regional subdomains
+const STV_HOSTS: &[&str] = &["serveme.tf"];
+
+static LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+fn link_regex() -> &'static Regex {
+    // links to the known hosts are often posted without a scheme
+    LINK_REGEX.get_or_init(|| {
+        Regex::new(r#"https?://[^\s"'<>]+|\b(?:[\w-]+\.)*(?:logs|demos|serveme)\.tf/[^\s"'<>]+"#)
+            .unwrap()
+    })
+}
+
+fn is_stv_host(host: &str) -> bool {
+    STV_HOSTS.iter().any(|stv_host| {
+        host == *stv_host
+            || host
+                .strip_suffix(stv_host)
+                .is_some_and(|subdomain| subdomain.ends_with('.'))
+    })
+}
+
+/// Recognize links to logs.tf, demos.tf and stv demo downloads
+fn external_link(url: &str) -> Option<ExternalLink> {
+    let url = url.trim_end_matches(['.', ',', ')']);
+    let (url, rest) = match url.split_once("://") {
+        Some((_, rest)) => (url.to_string(), rest),
+        None => (format!("https://{url}"), url),
+    };
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = host.trim_start_matches("www.").to_ascii_lowercase();
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let (kind, id) = match host.as_str() {
+        "logs.tf" | "demos.tf" => {
+            let id = path.trim_start_matches("logs/").trim_end_matches('/');
+            if id.is_empty() || !id.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let kind = if host == "logs.tf" {
+                ExternalLinkKind::Logs
+            } else {
+                ExternalLinkKind::Demos
+            };
+            (kind, id)
+        }
+        _ => {
+            let file = path.rsplit('/').next().unwrap_or_default();
+            let is_demo = [".dem", ".zip", ".bz2", ".7z"]
+                .iter()
+                .any(|extension| file.to_ascii_lowercase().ends_with(extension));
+            if !is_demo || !is_stv_host(&host) {
+                return None;
+            }
+            (ExternalLinkKind::Stv, file)
+        }
+    };
+    Some(ExternalLink {
+        kind,
+        id: id.to_string(),
+        url,
+    })
+}
+
+#[derive(Clone)]
 pub struct MatchPageParser {
     selector_format: Selector,
-    selector_author: Selector,
-    selector_comment: Selector,
     selector_team_link: Selector,
     selector_result_team: Selector,
     selector_result_score: Selector,
     selector_map: Selector,
     selector_week: Selector,
     selector_date: Selector,
+    selector_result: Selector,
+    selector_result_winner: Selector,
+    selector_result_scores: Selector,
+    selector_result_header: Selector,
+    selector_comments: Selector,
+    selector_comments_author: Selector,
+    selector_content_link: Selector,
 }
 
 impl Default for MatchPageParser {
@@ -38,16 +123,115 @@ impl MatchPageParser {
     pub fn new() -> Self {
         MatchPageParser {
             selector_format: Selector::parse(SELECTOR_MATCH_FORMAT).unwrap(),
-            selector_author: Selector::parse(SELECTOR_MATCH_COMMENT_AUTHOR).unwrap(),
-            selector_comment: Selector::parse(SELECTOR_MATCH_COMMENT).unwrap(),
             selector_team_link: Selector::parse(SELECTOR_MATCH_TEAM_LINK).unwrap(),
             selector_result_team: Selector::parse(SELECTOR_MATCH_RESULT_TEAM).unwrap(),
             selector_result_score: Selector::parse(SELECTOR_MATCH_RESULT_SCORE).unwrap(),
             selector_map: Selector::parse(SELECTOR_MATCH_MAP).unwrap(),
             selector_week: Selector::parse(SELECTOR_MATCH_WEEK).unwrap(),
             selector_date: Selector::parse(SELECTOR_MATCH_DATE).unwrap(),
+            selector_result: Selector::parse(SELECTOR_MATCH_RESULT).unwrap(),
+            selector_result_winner: Selector::parse(SELECTOR_MATCH_RESULT_WINNER).unwrap(),
+            selector_result_scores: Selector::parse(SELECTOR_MATCH_RESULT_SCORES).unwrap(),
+            selector_result_header: Selector::parse(SELECTOR_MATCH_RESULT_HEADER).unwrap(),
+            selector_comments: Selector::parse(SELECTOR_MATCH_COMMENTS).unwrap(),
+            selector_comments_author: Selector::parse(SELECTOR_MATCH_COMMENTS_AUTHOR).unwrap(),
+            selector_content_link: Selector::parse(SELECTOR_MATCH_CONTENT_LINK).unwrap(),
+        }
+    }
+
+    /// Scores of the individual rounds, listed after "Score by rounds:"
+    fn rounds(&self, result: ElementRef) -> Result<Vec<RoundScore>> {
+        let Some(scores) = result.select(&self.selector_result_scores).next() else {
+            return Ok(Vec::new());
+        };
+        let text: String = scores.text().collect();
+        let Some((_, rounds)) = text.split_once("Score by rounds:") else {
+            return Ok(Vec::new());
+        };
+        let rounds = rounds.split("Matchpoints").next().unwrap_or_default();
+        round_regex()
+            .captures_iter(rounds)
+            .map(|round| {
+                let parse = |index: usize| {
+                    round[index].parse().map_err(|_| ParseError::InvalidText {
+                        text: round[0].to_string(),
+                        role: "round score",
+                    })
+                };
+                Ok(RoundScore {
+                    home: parse(1)?,
+                    away: parse(2)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Text of the result label, winner and scores, without the write-up comments below them
+    fn result_header(&self, result: ElementRef) -> String {
+        result
+            .select(&self.selector_result_header)
+            .flat_map(|header| header.text())
+            .collect::<String>()
+            .to_lowercase()
+    }
+
+    fn forfeit(&self, result: ElementRef, header: &str, team_name_home: &str) -> Option<Forfeit> {
+        if !header.contains("forfeit") {
+            return None;
+        }
+        if header.contains("double forfeit") {
+            return Some(Forfeit::Double);
+        }
+        // the winning team is listed with the result
+        let winner = select_text(result, &self.selector_result_winner)?;
+        if winner == team_name_home {
+            Some(Forfeit::Away)
+        } else {
+            Some(Forfeit::Home)
         }
     }
+
+    fn comment(&self, comment: ElementRef) -> Option<MatchComment> {
+        let author = comment.select(&self.selector_comments_author).next();
+
+        let mut body = String::new();
+        for child in comment.children() {
+            match child.value() {
+                Node::Text(text) => body.push_str(text),
+                Node::Element(element) if element.name() == "br" => body.push('\n'),
+                Node::Element(element) if element.name() == "small" => {}
+                Node::Element(_) => {
+                    // the author is listed in a span in front of the comment
+                    let Some(element) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if element
+                        .select(&self.selector_comments_author)
+                        .next()
+                        .is_none()
+                    {
+                        body.extend(element.text());
+                    }
+                }
+                _ => {}
+            }
+        }
+        let body = body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if body.is_empty() {
+            return None;
+        }
+        Some(MatchComment {
+            author: author
+                .and_then(|author| author.first_text())
+                .map(|author| author.trim_end_matches(':').trim().to_string()),
+            body,
+        })
+    }
 }
 
 impl Parser for MatchPageParser {
@@ -56,9 +240,6 @@ impl Parser for MatchPageParser {
     fn parse(&self, document: &str) -> Result<Self::Output> {
         let document = Html::parse_document(document);
 
-        let author = select_text(document.root_element(), &self.selector_author);
-        let comment = select_last_text(document.root_element(), &self.selector_comment);
-
         let mut team_links = document.select(&self.selector_team_link);
         let team_link_home = team_links.next().ok_or(ParseError::ElementNotFound {
             selector: SELECTOR_MATCH_TEAM_LINK,
@@ -166,9 +347,38 @@ impl Parser for MatchPageParser {
                 text: "dont have this".to_string(),
             })?;
 
+        let (rounds, forfeit, no_show) = match document.select(&self.selector_result).next() {
+            Some(result) => {
+                let header = self.result_header(result);
+                (
+                    self.rounds(result)?,
+                    self.forfeit(result, &header, &team_name_home),
+                    header.contains("no show") || header.contains("no-show"),
+                )
+            }
+            None => (Vec::new(), None, false),
+        };
+        let comments: Vec<_> = document
+            .select(&self.selector_comments)
+            .filter_map(|comment| self.comment(comment))
+            .collect();
+
+        // logs are usually posted as plain text in the comments
+        let comment_urls = comments
+            .iter()
+            .flat_map(|comment| link_regex().find_iter(&comment.body))
+            .map(|url| url.as_str());
+        let link_urls = document
+            .select(&self.selector_content_link)
+            .filter_map(|link| link.attr("href"));
+        let mut links: Vec<ExternalLink> = Vec::new();
+        for link in link_urls.chain(comment_urls).filter_map(external_link) {
+            if !links.iter().any(|existing| existing.url == link.url) {
+                links.push(link);
+            }
+        }
+
         Ok(MatchInfo {
-            comment_author: author.map(String::from),
-            comment: comment.map(String::from),
             score_away: team_score_away,
             score_home: team_score_home,
             team_home: TeamRef {
@@ -181,8 +391,83 @@ impl Parser for MatchPageParser {
             },
             week,
             map: map.into(),
-            default_date: date.into(),
+            default_date: MatchDate::new(date),
             format,
+            rounds,
+            forfeit,
+            no_show,
+            comments,
+            links,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(text: &str) -> Vec<ExternalLink> {
+        link_regex()
+            .find_iter(text)
+            .filter_map(|url| external_link(url.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_bare_links() {
+        let found = links("logs: logs.tf/3509421, demo at www.demos.tf/1234.");
+        assert_eq!(
+            found,
+            [
+                ExternalLink {
+                    kind: ExternalLinkKind::Logs,
+                    id: "3509421".into(),
+                    url: "https://logs.tf/3509421".into(),
+                },
+                ExternalLink {
+                    kind: ExternalLinkKind::Demos,
+                    id: "1234".into(),
+                    url: "https://www.demos.tf/1234".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stv_hosts() {
+        let found = links(
+            "https://na.serveme.tf/uploads/auto-20231010-match.zip \
+             https://example.com/demos/match.dem na.serveme.tf/uploads/other.zip",
+        );
+        assert_eq!(
+            found,
+            [
+                ExternalLink {
+                    kind: ExternalLinkKind::Stv,
+                    id: "auto-20231010-match.zip".into(),
+                    url: "https://na.serveme.tf/uploads/auto-20231010-match.zip".into(),
+                },
+                ExternalLink {
+                    kind: ExternalLinkKind::Stv,
+                    id: "other.zip".into(),
+                    url: "https://na.serveme.tf/uploads/other.zip".into(),
+                },
+            ]
+        );
+        assert!(!is_stv_host("notserveme.tf"));
+    }
+
+    #[test]
+    fn test_forfeit_in_comment() {
+        let body = std::fs::read_to_string("tests/data/match_116246.html").unwrap();
+        let body = body.replacen(
+            "<h5>\nMatch Write-up\n</h5>",
+            "<h5>\nMatch Write-up\n</h5>\n<p>last week was a forfeit, they were a no show</p>",
+            1,
+        );
+        let parsed = MatchPageParser::new().parse(&body).unwrap();
+        assert!(parsed.comments[0].body.contains("forfeit"));
+        assert_eq!(parsed.forfeit, None);
+        assert!(!parsed.no_show);
+    }
+}
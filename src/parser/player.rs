@@ -30,6 +30,7 @@ const SELECTOR_AVATAR: &str =
 const SELECTOR_CLASS: &str =
     r#"img.img-rounded[src*="images/tf2/icon/"], img.img-rounded[data-cfsrc*="images/tf2/icon/"]"#;
 
+#[derive(Clone)]
 pub struct PlayerParser {
     selector_name: Selector,
     selector_id: Selector,
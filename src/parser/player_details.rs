@@ -13,6 +13,7 @@ const SELECTOR_TEAM_DIVISION: &str = "td:nth-child(3) small";
 const SELECTOR_TEAM_JOINED: &str = "td:nth-child(5) span";
 const SELECTOR_TEAM_LEFT: &str = "td:nth-child(6) span";
 
+#[derive(Clone)]
 pub struct PlayerDetailsParser {
     selector_team_format: Selector,
     selector_team_group: Selector,
@@ -94,7 +95,8 @@ impl Parser for PlayerDetailsParser {
                 let id = team_id_from_link(link)?;
 
                 Ok(MembershipHistory {
-                    format: format.to_string(),
+                    format: format.parse().ok(),
+                    league: format.to_string(),
                     joined: Date::parse(joined, DATE_FORMAT).map_err(|_| {
                         ParseError::InvalidDate {
                             role: "team join date",
@@ -106,7 +108,7 @@ impl Parser for PlayerDetailsParser {
                         name: name.to_string(),
                         id,
                     },
-                    division: division.to_string(),
+                    division: division.into(),
                 })
             })
             .collect()
@@ -1,5 +1,5 @@
 use super::Parser;
-use crate::data::{Season, Seasons};
+use crate::data::{Season, SeasonId, Seasons};
 use crate::parser::{select_text, ElementExt};
 use crate::{ParseError, Result};
 use scraper::{Html, Selector};
@@ -8,6 +8,7 @@ const SELECTOR_MENU: &str = ".sub-menu";
 const SELECTOR_NAME: &str = ".mega-menu-sub-title";
 const SELECTOR_SEASON_LINK: &str = "ul[id$=\"seasons\"] a[href^=\"rankings_\"]";
 
+#[derive(Clone)]
 pub struct SeasonsParser {
     selector_menu: Selector,
     selector_name: Selector,
@@ -64,18 +65,21 @@ impl Parser for SeasonsParser {
                             role: "season link",
                             selector: SELECTOR_SEASON_LINK,
                         })?;
-                        let id = link
-                            .trim_start_matches("rankings_")
-                            .trim_end_matches(".cfm");
+                        let id: SeasonId = link.parse().map_err(|_| ParseError::InvalidLink {
+                            role: "season link",
+                            link: link.to_string(),
+                        })?;
                         Ok(Season {
                             name: text.to_string(),
-                            id: id.to_string(),
+                            id,
                         })
                     })
-                    .collect::<Result<_>>()?;
+                    .collect::<Result<Vec<_>>>()?;
+                let format = seasons.first().map(|season| season.id.format);
 
                 Ok(Seasons {
                     mode: name.to_string(),
+                    format,
                     seasons,
                 })
             })
@@ -1,5 +1,5 @@
 use super::{select_text_empty, whitespace_regex, ElementExt, Parser};
-use crate::data::{Membership, NameChange, Record, Team};
+use crate::data::{Division, Membership, NameChange, Record, Team};
 use crate::parser::{
     select_text, steam_id_from_link, DATE_FORMAT, MEMBER_DATE_ALT_FORMAT, MEMBER_DATE_FORMAT,
 };
@@ -43,6 +43,7 @@ const SELECTOR_TEAM_NAME_DATE: &str = "td:nth-child(5) small";
 
 const SELECTOR_STEAM: &str = r#"a.btn.btn-xs.btn-default[href*="//steamcommunity.com/groups"]"#;
 
+#[derive(Clone)]
 pub struct TeamParser {
     selector_name: Selector,
     selector_tag: Selector,
@@ -220,12 +221,12 @@ impl Parser for TeamParser {
                         role: "team record season",
                     },
                 )?;
-                let division = select_text(record, &self.selector_team_record_division)
+                let division: Division = select_text(record, &self.selector_team_record_division)
                     .ok_or(ParseError::ElementNotFound {
                         selector: SELECTOR_TEAM_RECORD_DIVISION,
                         role: "team record division",
                     })?
-                    .to_string();
+                    .into();
                 let result = select_text(record, &self.selector_team_record_result).ok_or(
                     ParseError::ElementNotFound {
                         selector: SELECTOR_TEAM_RECORD_RESULT,
@@ -367,7 +368,7 @@ impl Parser for TeamParser {
         Ok(Team {
             name,
             description,
-            division,
+            division: division.as_str().into(),
             timezone,
             format,
             steam_group,
@@ -7,6 +7,7 @@ use scraper::{Html, Selector};
 const SELECTOR_SELECT: &str = "select[name=\"clan_select\"]";
 const SELECTOR_OPTION: &str = "option[value^=\"team_page\"]";
 
+#[derive(Clone)]
 pub struct TeamLookupParser {
     selector_select: Selector,
     selector_option: Selector,
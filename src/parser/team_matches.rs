@@ -1,10 +1,12 @@
 use super::Parser;
-use crate::data::{GameMode, MatchResult, TeamRef, TeamSeason, TeamSeasonMatch};
+use crate::data::{
+    GameMode, MatchDate, MatchResult, SeasonId, TeamRef, TeamSeason, TeamSeasonMatch,
+};
 use crate::parser::{match_id_from_link, select_text, team_id_from_link, ElementExt};
 use crate::{ParseError, Result};
 use scraper::{Html, Selector};
 use std::str::FromStr;
-use ugc_scraper_types::{Side, TeamMatches};
+use ugc_scraper_types::{Outcome, Region, Side, TeamMatches};
 
 const SELECTOR_SEASON_TITLE: &str = ".container table.table.table-condensed.table-striped thead h4";
 const SELECTOR_SEASON_SEASON: &str =
@@ -26,6 +28,7 @@ const SELECTOR_SEASON_MATCH_PAGE: &str = "td a[href^=\"matchpage\"]";
 const SELECTOR_TEAM_NAME: &str = r#"div.col-md-9 > h2 > b"#;
 const SELECTOR_TEAM_LINK: &str = r#"h2 > span.pull-right > a[href^="team_page.cfm"]"#;
 
+#[derive(Clone)]
 pub struct TeamMatchesParser {
     selector_title: Selector,
     selector_season: Selector,
@@ -87,16 +90,16 @@ impl Parser for TeamMatchesParser {
             .zip(document.select(&self.selector_season))
             .zip(document.select(&self.selector_matches))
             .map(|((title, season), matches)| {
-                let format = title.first_text().ok_or(ParseError::EmptyText {
+                let title_text = title.first_text().ok_or(ParseError::EmptyText {
                     selector: SELECTOR_SEASON_TITLE,
                     role: "season title",
                 })?;
 
-                let format = format
+                let format = title_text
                     .split(' ')
                     .find_map(|part| GameMode::from_str(part).ok())
                     .ok_or(ParseError::InvalidText {
-                        text: format.into(),
+                        text: title_text.into(),
                         role: "season format",
                     })?;
 
@@ -104,12 +107,29 @@ impl Parser for TeamMatchesParser {
                     selector: SELECTOR_SEASON_SEASON,
                     role: "season title",
                 })?;
-                let season: u32 = season.trim_start_matches("Season ").parse().map_err(|_| {
-                    ParseError::InvalidText {
+                let season =
+                    SeasonId::number_from_header(season).ok_or(ParseError::InvalidText {
                         text: season.to_string(),
                         role: "season title",
-                    }
-                })?;
+                    })?;
+                // the region is only mentioned in the title, e.g. "ASIA TF2 Highlander"
+                let id = SeasonId {
+                    region: title_text.contains("ASIA").then_some(Region::Asia),
+                    ..SeasonId::new(format, season)
+                };
+
+                // forfeits get the full points for a win, which vary between seasons
+                let win_points = matches
+                    .select(&self.selector_match)
+                    .flat_map(|game| {
+                        [
+                            select_text(game, &self.selector_points),
+                            select_text(game, &self.selector_points_opponent),
+                        ]
+                    })
+                    .flatten()
+                    .filter_map(|points| points.parse::<f32>().ok())
+                    .fold(0.0, f32::max);
 
                 let matches = matches
                     .select(&self.selector_match)
@@ -190,8 +210,19 @@ impl Parser for TeamMatchesParser {
                                     text: scores.to_string(),
                                     role: "match scores",
                                 })?;
-                        let score: u8 = score.trim().parse().unwrap_or_default();
-                        let score_opponent: u8 = score_opponent.trim().parse().unwrap_or_default();
+                        // scores are left empty until the match is reported
+                        let parse_score = |score: &str| match score.trim() {
+                            "" => Ok(None),
+                            score => score
+                                .parse()
+                                .map(Some)
+                                .map_err(|_| ParseError::InvalidText {
+                                    text: scores.to_string(),
+                                    role: "match scores",
+                                }),
+                        };
+                        let score: Option<u8> = parse_score(score)?;
+                        let score_opponent: Option<u8> = parse_score(score_opponent)?;
 
                         let opponent = opponent_link
                             .map(|link| {
@@ -216,6 +247,13 @@ impl Parser for TeamMatchesParser {
                                     score_opponent,
                                     match_points: point,
                                     match_points_opponent: points_opponent,
+                                    outcome: Outcome::from_scores(
+                                        score,
+                                        score_opponent,
+                                        point,
+                                        points_opponent,
+                                        win_points,
+                                    ),
                                 }
                             }
                             (Some(opponent), None, None, Some(_)) => MatchResult::Pending {
@@ -236,7 +274,7 @@ impl Parser for TeamMatchesParser {
                         };
                         Ok(TeamSeasonMatch {
                             week,
-                            date: date.to_string(),
+                            date: MatchDate::new(date),
                             side: side.parse::<Side>().map_err(|error| {
                                 ParseError::InvalidText {
                                     text: error.text,
@@ -244,13 +282,14 @@ impl Parser for TeamMatchesParser {
                                 }
                             })?,
                             map: map.to_string(),
-                            division: division.to_string(),
+                            division: division.into(),
                             result,
                         })
                     })
                     .collect::<Result<_>>()?;
 
                 Ok(TeamSeason {
+                    id,
                     season,
                     matches,
                     format,
@@ -16,6 +16,7 @@ const SELECTOR_ROSTER_LEFT: &str = "span.text-danger small";
 
 const SELECTOR_STEAM: &str = r#"p.muted a[href*="//steamcommunity.com/groups"]"#;
 
+#[derive(Clone)]
 pub struct TeamRosterHistoryParser {
     selector_item: Selector,
     selector_name: Selector,
@@ -1,23 +1,30 @@
 use super::Parser;
-use crate::data::{TeamRef, Transaction};
+use crate::data::{Division, TeamRef, Transaction};
 use crate::parser::{
     select_last_text, select_text, steam_id_from_link, team_id_from_link, ElementExt,
+    MEMBER_DATE_ALT_FORMAT,
 };
 use crate::{ParseError, Result};
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+use time::Date;
 
 const SELECTOR_TRANSACTION_ROW: &str = "table.table.table-condensed.table-striped tr";
 const SELECTOR_TRANSACTION_PLAYER_LINK: &str = "a[href^=\"players_page\"][title^=\"Roster\"]";
 const SELECTOR_TRANSACTION_ACTION: &str = "td:nth-child(4) span b";
 const SELECTOR_TRANSACTION_TEAM_LINK: &str = "a[href^=\"team_page\"]";
 const SELECTOR_TRANSACTION_TEAM_NAME: &str = "td:nth-child(5)";
+const SELECTOR_TRANSACTION_DATE: &str = "td:nth-child(6) small";
+const SELECTOR_TRANSACTION_DIVISION: &str = "td h4";
 
+#[derive(Clone)]
 pub struct TransactionParser {
     selector_row: Selector,
     selector_player: Selector,
     selector_action: Selector,
     selector_team_link: Selector,
     selector_team_name: Selector,
+    selector_date: Selector,
+    selector_division: Selector,
 }
 
 impl Default for TransactionParser {
@@ -34,8 +41,68 @@ impl TransactionParser {
             selector_action: Selector::parse(SELECTOR_TRANSACTION_ACTION).unwrap(),
             selector_team_link: Selector::parse(SELECTOR_TRANSACTION_TEAM_LINK).unwrap(),
             selector_team_name: Selector::parse(SELECTOR_TRANSACTION_TEAM_NAME).unwrap(),
+            selector_date: Selector::parse(SELECTOR_TRANSACTION_DATE).unwrap(),
+            selector_division: Selector::parse(SELECTOR_TRANSACTION_DIVISION).unwrap(),
         }
     }
+
+    fn parse_transaction(&self, row: ElementRef, division: &Division) -> Result<Transaction> {
+        let player_link =
+            row.select(&self.selector_player)
+                .next()
+                .ok_or(ParseError::ElementNotFound {
+                    selector: SELECTOR_TRANSACTION_PLAYER_LINK,
+                    role: "player link",
+                })?;
+        let name = player_link.first_text().ok_or(ParseError::EmptyText {
+            selector: SELECTOR_TRANSACTION_PLAYER_LINK,
+            role: "player name",
+        })?;
+        let steam_id = steam_id_from_link(player_link.attr("href").unwrap_or_default())?;
+
+        let action = select_text(row, &self.selector_action)
+            .ok_or(ParseError::ElementNotFound {
+                selector: SELECTOR_TRANSACTION_ACTION,
+                role: "transaction action",
+            })?
+            .parse()?;
+
+        let team_link =
+            row.select(&self.selector_team_link)
+                .next()
+                .ok_or(ParseError::ElementNotFound {
+                    selector: SELECTOR_TRANSACTION_TEAM_LINK,
+                    role: "team link",
+                })?;
+        let team_id = team_id_from_link(team_link.attr("href").unwrap_or_default())?;
+        let team_name =
+            select_last_text(row, &self.selector_team_name).ok_or(ParseError::EmptyText {
+                selector: SELECTOR_TRANSACTION_TEAM_LINK,
+                role: "team link",
+            })?;
+
+        let date = select_text(row, &self.selector_date).ok_or(ParseError::ElementNotFound {
+            selector: SELECTOR_TRANSACTION_DATE,
+            role: "transaction date",
+        })?;
+        let date =
+            Date::parse(date, MEMBER_DATE_ALT_FORMAT).map_err(|_| ParseError::InvalidDate {
+                role: "transaction date",
+                date: date.to_string(),
+            })?;
+
+        Ok(Transaction {
+            name: name.to_string(),
+            steam_id,
+            action,
+            team: TeamRef {
+                id: team_id,
+                name: team_name.to_string(),
+            },
+            division: division.clone(),
+            date,
+        })
+    }
 }
 
 impl Parser for TransactionParser {
@@ -44,53 +111,18 @@ impl Parser for TransactionParser {
     fn parse(&self, document: &str) -> Result<Self::Output> {
         let document = Html::parse_document(document);
 
-        document
-            .select(&self.selector_row)
-            .filter(|row| row.select(&self.selector_player).next().is_some())
-            .map(|row| {
-                let player_link = row.select(&self.selector_player).next().ok_or(
-                    ParseError::ElementNotFound {
-                        selector: SELECTOR_TRANSACTION_PLAYER_LINK,
-                        role: "player link",
-                    },
-                )?;
-                let name = player_link.first_text().ok_or(ParseError::EmptyText {
-                    selector: SELECTOR_TRANSACTION_PLAYER_LINK,
-                    role: "player name",
-                })?;
-                let steam_id = steam_id_from_link(player_link.attr("href").unwrap_or_default())?;
-
-                let action = select_text(row, &self.selector_action)
-                    .ok_or(ParseError::ElementNotFound {
-                        selector: SELECTOR_TRANSACTION_ACTION,
-                        role: "transaction action",
-                    })?
-                    .parse()?;
-
-                let team_link = row.select(&self.selector_team_link).next().ok_or(
-                    ParseError::ElementNotFound {
-                        selector: SELECTOR_TRANSACTION_TEAM_LINK,
-                        role: "team link",
-                    },
-                )?;
-                let team_id = team_id_from_link(team_link.attr("href").unwrap_or_default())?;
-                let team_name = select_last_text(row, &self.selector_team_name).ok_or(
-                    ParseError::EmptyText {
-                        selector: SELECTOR_TRANSACTION_TEAM_LINK,
-                        role: "team link",
-                    },
-                )?;
-
-                Ok(Transaction {
-                    name: name.to_string(),
-                    steam_id,
-                    action,
-                    team: TeamRef {
-                        id: team_id,
-                        name: team_name.to_string(),
-                    },
-                })
-            })
-            .collect()
+        // transactions are grouped by division, with a header row before each group
+        let mut division = Division::from("");
+        let mut transactions = Vec::new();
+        for row in document.select(&self.selector_row) {
+            if row.attr("class") == Some("top-bar") {
+                if let Some(name) = select_text(row, &self.selector_division) {
+                    division = Division::from(name);
+                }
+            } else if row.select(&self.selector_player).next().is_some() {
+                transactions.push(self.parse_transaction(row, &division)?);
+            }
+        }
+        Ok(transactions)
     }
 }
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, Instant};
+use tracing::trace;
+
+/// Politeness policy for requests send to the ugc website
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+    concurrency: usize,
+    jitter: f64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            requests_per_second: 2.0,
+            burst: 2,
+            concurrency: 2,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RateLimit {
+    pub fn new(requests_per_second: f64) -> Self {
+        RateLimit {
+            requests_per_second: requests_per_second.max(0.001),
+            ..RateLimit::default()
+        }
+    }
+
+    /// Number of requests that can be send in quick succession after the client has been idle
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Maximum number of in-flight requests to a single host
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Randomize waits by up to this fraction to prevent requests from lining up
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Token bucket rate limiter with per-host concurrency limits.
+///
+/// Clones share the same bucket, so a single limiter can be used by multiple clients.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<RateLimiterInner>,
+}
+
+#[derive(Debug)]
+struct RateLimiterInner {
+    config: RateLimit,
+    bucket: Mutex<Bucket>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Keeps the per-host concurrency slot claimed until dropped
+pub struct RateLimitPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimit::default())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimit) -> Self {
+        RateLimiter {
+            inner: Arc::new(RateLimiterInner {
+                bucket: Mutex::new(Bucket {
+                    tokens: config.burst as f64,
+                    last_refill: Instant::now(),
+                }),
+                hosts: Mutex::default(),
+                config,
+            }),
+        }
+    }
+
+    /// Wait until a request to `host` is allowed
+    pub async fn acquire(&self, host: &str) -> RateLimitPermit {
+        let semaphore = self.host_semaphore(host);
+        let permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("rate limit semaphore is never closed");
+
+        while let Some(wait) = self.take_token() {
+            let wait = jittered(wait, self.inner.config.jitter);
+            trace!(host, ?wait, "waiting for rate limit");
+            sleep(wait).await;
+        }
+
+        RateLimitPermit { _permit: permit }
+    }
+
+    fn host_semaphore(&self, host: &str) -> Arc<Semaphore> {
+        let mut hosts = self.inner.hosts.lock().unwrap();
+        hosts
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.inner.config.concurrency)))
+            .clone()
+    }
+
+    /// Take a token from the bucket, or return how long to wait before the next one is available
+    fn take_token(&self) -> Option<Duration> {
+        let config = &self.inner.config;
+        let mut bucket = self.inner.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * config.requests_per_second).min(config.burst as f64);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / config.requests_per_second,
+            ))
+        }
+    }
+}
+
+/// Extend a duration by a random amount of up to `jitter` times the original duration
+pub(crate) fn jittered(duration: Duration, jitter: f64) -> Duration {
+    duration.mul_f64(1.0 + fastrand::f64() * jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::advance;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst() {
+        let limiter = RateLimiter::new(RateLimit::new(1.0).burst(3));
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), Some(Duration::from_secs(1)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refill() {
+        let limiter = RateLimiter::new(RateLimit::new(2.0).burst(2));
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), Some(Duration::from_millis(500)));
+
+        advance(Duration::from_millis(500)).await;
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), Some(Duration::from_millis(500)));
+
+        // refilling never exceeds the burst size
+        advance(Duration::from_secs(60)).await;
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), None);
+        assert!(limiter.take_token().is_some());
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let base = Duration::from_secs(1);
+        for _ in 0..100 {
+            let wait = jittered(base, 0.5);
+            assert!(wait >= base);
+            assert!(wait <= Duration::from_millis(1500));
+        }
+    }
+}
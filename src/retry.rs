@@ -0,0 +1,197 @@
+use crate::rate_limit::jittered;
+use crate::ScrapeError;
+use reqwest::StatusCode;
+use std::time::Duration;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+
+/// Policy for retrying failed requests
+///
+/// Requests are retried on network errors, `429 Too Many Requests` and server errors
+/// with an exponential backoff, honoring the `Retry-After` header when the server sends one.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retry policy with the default delays, making up to `max_attempts` attempts
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Never retry failed requests
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    /// Delay before the first retry, doubled for every following attempt
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Upper limit for the delay between attempts, also applied to `Retry-After`
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Randomize the backoff by up to this fraction
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the delay before the next attempt, or `None` if the failed attempt shouldn't be retried
+    pub fn retry_delay(&self, attempt: u32, error: &ScrapeError) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        match error {
+            ScrapeError::Request(_) => Some(self.backoff(attempt)),
+            ScrapeError::Status {
+                status,
+                retry_after,
+            } if *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() => {
+                Some(match retry_after {
+                    Some(retry_after) => (*retry_after).min(self.max_delay),
+                    None => self.backoff(attempt),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self.base_delay.saturating_mul(1 << exponent);
+        jittered(delay.min(self.max_delay), self.jitter)
+    }
+}
+
+/// Parse a `Retry-After` header value, which can either be a number of seconds or an http date
+pub(crate) fn parse_retry_after(value: &str, now: OffsetDateTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    (date - now).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn server_error() -> ScrapeError {
+        ScrapeError::Status {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff_sequence() {
+        let policy = RetryPolicy::new(6)
+            .base_delay(Duration::from_millis(100))
+            .jitter(0.0);
+        let delays: Vec<_> = (1..6)
+            .map(|attempt| policy.retry_delay(attempt, &server_error()))
+            .collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1600]
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .to_vec()
+        );
+        assert_eq!(policy.retry_delay(6, &server_error()), None);
+    }
+
+    #[test]
+    fn test_max_delay() {
+        let policy = RetryPolicy::new(40)
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5))
+            .jitter(0.0);
+        assert_eq!(
+            policy.retry_delay(3, &server_error()),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(
+            policy.retry_delay(4, &server_error()),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            policy.retry_delay(39, &server_error()),
+            Some(Duration::from_secs(5))
+        );
+
+        let retry_after = ScrapeError::Status {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            retry_after: Some(Duration::from_secs(120)),
+        };
+        assert_eq!(
+            policy.retry_delay(1, &retry_after),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn test_no_retry_for_client_errors() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.retry_delay(1, &ScrapeError::NotFound), None);
+        let forbidden = ScrapeError::Status {
+            status: StatusCode::FORBIDDEN,
+            retry_after: None,
+        };
+        assert_eq!(policy.retry_delay(1, &forbidden), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        let now = datetime!(2015-10-21 07:20:00 UTC);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after_date() {
+        let now = datetime!(2015-10-21 07:20:00 UTC);
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(480))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 +0000", now),
+            Some(Duration::from_secs(480))
+        );
+        // dates in the past can't be converted into a wait
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            None
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use crate::page::Page;
+use crate::source::PageSource;
+use crate::{Result, ScrapeError};
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Load pages from a directory of saved html files, using the same naming as `tests/data`
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    directory: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        DirectorySource {
+            directory: directory.into(),
+        }
+    }
+
+    /// Path the html for a page is stored at
+    pub fn path(&self, page: &Page) -> PathBuf {
+        self.directory.join(file_name(page))
+    }
+}
+
+#[async_trait]
+impl PageSource for DirectorySource {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        match tokio::fs::read_to_string(self.path(page)).await {
+            Ok(body) => Ok(body),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(ScrapeError::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn file_name(page: &Page) -> String {
+    match page {
+        Page::Player(steam_id) => format!("player_{}.html", u64::from(*steam_id)),
+        Page::PlayerDetails(steam_id) => format!("player_details_{}.html", u64::from(*steam_id)),
+        Page::Team(id) => format!("team_{id}.html"),
+        Page::TeamRosterHistory(id) => format!("team_roster_history_{id}.html"),
+        Page::TeamMatches(id) => format!("team_matches_{id}.html"),
+        Page::Seasons => "index.html".into(),
+        Page::TeamLookup(format) => format!("lookup_{format}.html"),
+        Page::Match(id) => format!("match_{id}.html"),
+        Page::Transactions(format) => format!("transactions_{format}.html"),
+        Page::MapHistory(format) => format!("map_{format}.html"),
+    }
+}
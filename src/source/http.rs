@@ -0,0 +1,197 @@
+use crate::cache::{CachedPage, ResponseCache};
+use crate::page::Page;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{parse_retry_after, RetryPolicy};
+use crate::source::{PageResponse, PageSource};
+use crate::{ParseError, Result, ScrapeError};
+use async_trait::async_trait;
+use reqwest::header::{
+    HeaderMap, HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{Client, StatusCode, Url};
+use std::time::{Duration, SystemTime};
+use time::OffsetDateTime;
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+/// Fetch pages from the live website
+#[derive(Clone)]
+pub struct HttpSource {
+    client: Client,
+    base_url: String,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: RetryPolicy,
+    cache: Option<ResponseCache>,
+}
+
+impl HttpSource {
+    pub(crate) fn new(
+        client: Client,
+        base_url: String,
+        rate_limiter: Option<RateLimiter>,
+        retry_policy: RetryPolicy,
+        cache: Option<ResponseCache>,
+    ) -> Self {
+        HttpSource {
+            client,
+            base_url,
+            rate_limiter,
+            retry_policy,
+            cache,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn try_request(&self, url: Url, cached: Option<&CachedPage>) -> Result<Fetched> {
+        let _permit = match &self.rate_limiter {
+            Some(rate_limiter) => Some(
+                rate_limiter
+                    .acquire(url.host_str().unwrap_or_default())
+                    .await,
+            ),
+            None => None,
+        };
+
+        let mut request = self.client.get(url);
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request.send().await?;
+        let status = response.status();
+
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (status, cached) {
+            return Ok(Fetched {
+                status,
+                retry_after: None,
+                page: CachedPage {
+                    fetched: SystemTime::now(),
+                    ..cached.clone()
+                },
+            });
+        }
+
+        let retry_after = retry_after(response.headers());
+        let etag = header_string(response.headers(), ETAG);
+        let last_modified = header_string(response.headers(), LAST_MODIFIED);
+        Ok(Fetched {
+            status,
+            retry_after,
+            page: CachedPage {
+                body: response.text().await?,
+                etag,
+                last_modified,
+                fetched: SystemTime::now(),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl PageSource for HttpSource {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        self.fetch_response(page).await?.into_result()
+    }
+
+    async fn fetch_response(&self, page: &Page) -> Result<PageResponse> {
+        let link = format!("{}{}", self.base_url, page.path());
+        let cached = match &self.cache {
+            Some(cache) => cache.get(&link).await,
+            None => None,
+        };
+        if let (Some(cache), Some(cached)) = (&self.cache, &cached) {
+            if cache.is_fresh(cached, page.kind()) {
+                debug!(url = link.as_str(), "using cached page");
+                return Ok(PageResponse {
+                    status: StatusCode::OK,
+                    body: cached.body.clone(),
+                });
+            }
+        }
+
+        let url = Url::parse(&link).map_err(|_| ParseError::InvalidLink {
+            link: link.clone(),
+            role: "page",
+        })?;
+        let mut attempt = 1;
+        let fetched = loop {
+            debug!(url = url.as_str(), attempt, "sending request");
+            let (error, response) = match self.try_request(url.clone(), cached.as_ref()).await {
+                Ok(fetched) if fetched.is_error() => (
+                    ScrapeError::Status {
+                        status: fetched.status,
+                        retry_after: fetched.retry_after,
+                    },
+                    Some(fetched),
+                ),
+                Ok(fetched) => break fetched,
+                Err(e) => (e, None),
+            };
+            match self.retry_policy.retry_delay(attempt, &error) {
+                Some(delay) => {
+                    warn!(
+                        url = url.as_str(),
+                        attempt,
+                        ?delay,
+                        error = ?error,
+                        "request failed, retrying"
+                    );
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                None => {
+                    return match response {
+                        Some(response) => Ok(response.into_response()),
+                        None => Err(error),
+                    }
+                }
+            }
+        };
+
+        if let Some(cache) = &self.cache {
+            if matches!(fetched.status, StatusCode::OK | StatusCode::NOT_MODIFIED) {
+                cache.insert(&link, fetched.page.clone()).await;
+            }
+        }
+        Ok(fetched.into_response())
+    }
+}
+
+/// A single response from the website
+struct Fetched {
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    page: CachedPage,
+}
+
+impl Fetched {
+    fn is_error(&self) -> bool {
+        self.status.is_client_error() || self.status.is_server_error()
+    }
+
+    fn into_response(self) -> PageResponse {
+        PageResponse {
+            status: self.status,
+            body: self.page.body,
+        }
+    }
+}
+
+fn header_string(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, OffsetDateTime::now_utc())
+}
@@ -0,0 +1,64 @@
+use crate::page::Page;
+use crate::{Result, ScrapeError};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+
+mod directory;
+mod http;
+mod recording;
+
+pub use directory::*;
+pub use http::*;
+pub use recording::*;
+
+/// Source of the html pages that get parsed by the client
+#[async_trait]
+pub trait PageSource: Send + Sync {
+    /// Fetch the html of a page, returning [`ScrapeError::NotFound`](crate::ScrapeError::NotFound) if it doesn't exist
+    async fn fetch(&self, page: &Page) -> Result<String>;
+
+    /// Fetch a page along with the status it was served with
+    ///
+    /// Sources that talk http return redirects and error responses as a response instead of an error,
+    /// other sources report every page they have as `200 OK`.
+    async fn fetch_response(&self, page: &Page) -> Result<PageResponse> {
+        Ok(PageResponse {
+            status: StatusCode::OK,
+            body: self.fetch(page).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl<S: PageSource + ?Sized> PageSource for std::sync::Arc<S> {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        (**self).fetch(page).await
+    }
+
+    async fn fetch_response(&self, page: &Page) -> Result<PageResponse> {
+        (**self).fetch_response(page).await
+    }
+}
+
+/// Html of a page and the status it was served with
+#[derive(Debug, Clone)]
+pub struct PageResponse {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl PageResponse {
+    /// Get the body of the page, turning redirects and error responses into the matching error
+    pub fn into_result(self) -> Result<String> {
+        if self.status == StatusCode::FOUND {
+            Err(ScrapeError::NotFound)
+        } else if self.status.is_client_error() || self.status.is_server_error() {
+            Err(ScrapeError::Status {
+                status: self.status,
+                retry_after: None,
+            })
+        } else {
+            Ok(self.body)
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::page::Page;
+use crate::source::{DirectorySource, PageSource};
+use crate::Result;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+/// Save every page fetched from the inner source into a directory.
+///
+/// The recorded pages can be served again using a [`DirectorySource`] for the same directory.
+pub struct RecordingSource<S> {
+    inner: S,
+    target: DirectorySource,
+}
+
+impl<S: PageSource> RecordingSource<S> {
+    pub fn new(inner: S, target: DirectorySource) -> Self {
+        RecordingSource { inner, target }
+    }
+}
+
+#[async_trait]
+impl<S: PageSource> PageSource for RecordingSource<S> {
+    async fn fetch(&self, page: &Page) -> Result<String> {
+        let body = self.inner.fetch(page).await?;
+        let path = self.target.path(page);
+        debug!(?page, path = %path.display(), "recording page");
+        if let Err(error) = tokio::fs::write(&path, &body).await {
+            warn!(path = %path.display(), %error, "failed to record page");
+        }
+        Ok(body)
+    }
+}
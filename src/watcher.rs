@@ -0,0 +1,312 @@
+use crate::data::{GameMode, RosterEvent, Transaction, TransactionAction};
+use crate::{Result, UgcClient};
+use async_trait::async_trait;
+use futures_util::{stream, Stream};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time::Date;
+use tokio::fs::{read_to_string, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::warn;
+
+/// Storage for the transactions a [`TransactionWatcher`] has already reported
+#[async_trait]
+pub trait TransactionStore: Send + Sync {
+    /// Mark a transaction as seen, returning `false` if it has been seen before
+    async fn insert(&self, transaction: &Transaction) -> Result<bool>;
+}
+
+#[async_trait]
+impl<S: TransactionStore + ?Sized> TransactionStore for Arc<S> {
+    async fn insert(&self, transaction: &Transaction) -> Result<bool> {
+        (**self).insert(transaction).await
+    }
+}
+
+/// Identifies a transaction independent of player or team renames
+///
+/// The feed has no ids or times for transactions, so a player joining or leaving the same team
+/// twice on one day is only reported once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TransactionKey {
+    steam_id: u64,
+    team: u32,
+    joined: bool,
+    date: Date,
+}
+
+impl TransactionKey {
+    fn new(transaction: &Transaction) -> Self {
+        TransactionKey {
+            steam_id: transaction.steam_id.into(),
+            team: transaction.team.id,
+            joined: matches!(transaction.action, TransactionAction::Joined),
+            date: transaction.date,
+        }
+    }
+
+    fn to_line(self) -> String {
+        let action = if self.joined { "joined" } else { "left" };
+        format!(
+            "{} {} {} {}\n",
+            self.steam_id,
+            self.team,
+            action,
+            self.date.to_julian_day()
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let steam_id = parts.next()?.parse().ok()?;
+        let team = parts.next()?.parse().ok()?;
+        let joined = match parts.next()? {
+            "joined" => true,
+            "left" => false,
+            _ => return None,
+        };
+        let date = Date::from_julian_day(parts.next()?.parse().ok()?).ok()?;
+        Some(TransactionKey {
+            steam_id,
+            team,
+            joined,
+            date,
+        })
+    }
+}
+
+/// Keeps seen transactions in memory, every transaction is reported again after a restart
+#[derive(Debug, Default)]
+pub struct MemoryTransactionStore {
+    seen: Mutex<HashSet<TransactionKey>>,
+}
+
+impl MemoryTransactionStore {
+    fn contains(&self, transaction: &Transaction) -> bool {
+        let seen = self.seen.lock().unwrap();
+        seen.contains(&TransactionKey::new(transaction))
+    }
+}
+
+#[async_trait]
+impl TransactionStore for MemoryTransactionStore {
+    async fn insert(&self, transaction: &Transaction) -> Result<bool> {
+        let mut seen = self.seen.lock().unwrap();
+        Ok(seen.insert(TransactionKey::new(transaction)))
+    }
+}
+
+/// Keeps seen transactions in a file, with one line appended for every new transaction
+#[derive(Debug)]
+pub struct FileTransactionStore {
+    path: PathBuf,
+    seen: MemoryTransactionStore,
+    /// Held from checking a transaction until it's persisted, so it's only written once
+    write: AsyncMutex<()>,
+}
+
+impl FileTransactionStore {
+    /// Load the seen transactions from `path`, a missing file is treated as empty
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let content = match read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let seen = content
+            .lines()
+            .filter_map(TransactionKey::from_line)
+            .collect();
+        Ok(FileTransactionStore {
+            path,
+            seen: MemoryTransactionStore {
+                seen: Mutex::new(seen),
+            },
+            write: AsyncMutex::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionStore for FileTransactionStore {
+    async fn insert(&self, transaction: &Transaction) -> Result<bool> {
+        let _write = self.write.lock().await;
+        if self.seen.contains(transaction) {
+            return Ok(false);
+        }
+        // only mark the transaction as seen once it's persisted, so a failed write is retried
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(TransactionKey::new(transaction).to_line().as_bytes())
+            .await?;
+        // tokio finishes the write in the background, make sure it's done before reporting it persisted
+        file.flush().await?;
+        self.seen.insert(transaction).await
+    }
+}
+
+/// Polls the roster transactions of a set of formats and reports every new transaction once
+///
+/// With an empty store, all transactions currently listed are reported on the first poll.
+pub struct TransactionWatcher {
+    client: UgcClient,
+    formats: Vec<GameMode>,
+    interval: Duration,
+    store: Arc<dyn TransactionStore>,
+}
+
+impl TransactionWatcher {
+    /// Watch the highlander, 6v6, 4v4 and ultiduo transactions every 5 minutes
+    pub fn new(client: UgcClient) -> Self {
+        TransactionWatcher {
+            client,
+            formats: vec![
+                GameMode::Highlander,
+                GameMode::Sixes,
+                GameMode::Fours,
+                GameMode::Ultiduo,
+            ],
+            interval: Duration::from_secs(5 * 60),
+            store: Arc::new(MemoryTransactionStore::default()),
+        }
+    }
+
+    /// Formats to watch the transactions of
+    pub fn formats(mut self, formats: impl IntoIterator<Item = GameMode>) -> Self {
+        self.formats = formats.into_iter().collect();
+        self
+    }
+
+    /// Time between polls of all formats, at least one second
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Store used to remember which transactions were already reported
+    pub fn store(mut self, store: impl TransactionStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Start polling, failed polls are logged and retried on the next interval
+    pub fn stream(self) -> impl Stream<Item = RosterEvent> + Send {
+        let mut ticker = interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        stream::unfold(
+            (self, ticker, VecDeque::new()),
+            |(watcher, mut ticker, mut pending)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (watcher, ticker, pending)));
+                    }
+                    watcher.poll(&mut ticker, &mut pending).await;
+                }
+            },
+        )
+    }
+
+    async fn poll(&self, ticker: &mut Interval, pending: &mut VecDeque<RosterEvent>) {
+        ticker.tick().await;
+        for &format in &self.formats {
+            let mut transactions = match self.client.transactions(format).await {
+                Ok(transactions) => transactions,
+                Err(error) => {
+                    warn!(
+                        ?error,
+                        %format,
+                        "failed to fetch transactions"
+                    );
+                    continue;
+                }
+            };
+            // the feed is grouped by division, report the oldest transactions first
+            transactions.sort_by_key(|transaction| transaction.date);
+            for transaction in transactions {
+                match self.store.insert(&transaction).await {
+                    Ok(true) => pending.push_back(RosterEvent {
+                        format,
+                        transaction,
+                    }),
+                    Ok(false) => {}
+                    Err(error) => {
+                        warn!(?error, "failed to store seen transaction");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Division, TeamRef};
+    use steamid_ng::SteamID;
+    use time::macros::date;
+
+    fn transaction() -> Transaction {
+        Transaction {
+            name: "Rhythm".into(),
+            steam_id: SteamID::from(76561198062801366),
+            action: TransactionAction::Joined,
+            team: TeamRef {
+                name: "el gato".into(),
+                id: 33014,
+            },
+            division: Division::from("Steel N.Amer"),
+            date: date!(2023 - 11 - 13),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store_failed_write() {
+        let store = FileTransactionStore::open("/nonexistent/seen.txt")
+            .await
+            .unwrap();
+        assert!(store.insert(&transaction()).await.is_err());
+        // not marked as seen without being persisted
+        assert!(store.insert(&transaction()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_persists() {
+        let path = std::env::temp_dir().join(format!("ugc-seen-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = FileTransactionStore::open(&path).await.unwrap();
+        assert!(store.insert(&transaction()).await.unwrap());
+        assert!(!store.insert(&transaction()).await.unwrap());
+
+        let reopened = FileTransactionStore::open(&path).await.unwrap();
+        assert!(!reopened.insert(&transaction()).await.unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_file_store_concurrent_insert() {
+        let path =
+            std::env::temp_dir().join(format!("ugc-seen-concurrent-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = Arc::new(FileTransactionStore::open(&path).await.unwrap());
+        let inserts = (0..8).map(|_| {
+            let store = store.clone();
+            tokio::spawn(async move { store.insert(&transaction()).await.unwrap() })
+        });
+        let inserted: Vec<bool> = futures_util::future::join_all(inserts)
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(inserted.iter().filter(|new| **new).count(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
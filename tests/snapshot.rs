@@ -9,6 +9,8 @@ use ugc_scraper::parser::{
     MapHistoryParser, MatchPageParser, Parser, PlayerDetailsParser, PlayerParser, SeasonsParser,
     TeamLookupParser, TeamMatchesParser, TeamParser, TeamRosterHistoryParser, TransactionParser,
 };
+#[cfg(feature = "serde")]
+use ugc_scraper::{Cassette, DirectorySource, UgcClient};
 
 #[test_case("player_76561198024494988.html", "player")]
 #[test_case("player_76561198049312442.html", "player_classes")]
@@ -121,3 +123,34 @@ fn test_parse_maps_html(input: &str, name: &str) {
     let parsed = parser.parse(&body).unwrap();
     assert_json_snapshot!(format!("parse_{name}_html"), parsed);
 }
+
+#[tokio::test]
+#[cfg(feature = "serde")]
+async fn test_directory_source() {
+    let client = UgcClient::with_source(DirectorySource::new("tests/data"));
+    let parsed = client.team(7861).await.unwrap();
+    assert_json_snapshot!("parse_team_html", parsed);
+}
+
+#[tokio::test]
+#[cfg(feature = "serde")]
+async fn test_team_matches_with_dates() {
+    let client = UgcClient::with_source(DirectorySource::new("tests/data"));
+    let mut parsed = client.team_matches_with_dates(7861).await.unwrap();
+    // the year of the current season is resolved relative to today
+    parsed.seasons.retain(|season| season.season != 41);
+    assert_json_snapshot!("team_matches_with_dates", parsed);
+}
+
+#[test_case("match_116246.json", "match")]
+#[tokio::test]
+#[cfg(feature = "serde")]
+async fn test_replay_cassette(input: &str, name: &str) {
+    let cassette = Cassette::load(format!("tests/cassettes/{input}")).unwrap();
+    let client = UgcClient::with_source(cassette.clone());
+    for interaction in cassette.interactions {
+        let (_, id) = interaction.url.rsplit_once('=').unwrap();
+        let parsed = client.match_info(id.parse().unwrap()).await.unwrap();
+        assert_json_snapshot!(format!("parse_{name}_html"), parsed);
+    }
+}
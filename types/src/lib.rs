@@ -1,12 +1,66 @@
 use serde::de::Error;
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 pub use steamid_ng::SteamID;
 use thiserror::Error;
 use time::error::Parse;
 use time::format_description::FormatItem;
 use time::macros::format_description;
-use time::{Date, OffsetDateTime};
+use time::parsing::Parsed;
+use time::{Date, Month, OffsetDateTime, Weekday};
+
+/// Match points compared by their bits, so types containing them can be used as keys
+#[derive(Debug, Clone, Copy)]
+struct TotalF32(f32);
+
+impl PartialEq for TotalF32 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TotalF32 {}
+
+impl PartialOrd for TotalF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalF32 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for TotalF32 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
+}
+
+/// Derive-like ordering over a key, for types holding a `SteamID` which isn't `Ord`
+macro_rules! ord_by_key {
+    ($ty:ty, |$value:ident| $key:expr) => {
+        impl PartialOrd for $ty {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $ty {
+            fn cmp(&self, other: &Self) -> Ordering {
+                let $value = self;
+                let lhs = $key;
+                let $value = other;
+                let rhs = $key;
+                lhs.cmp(&rhs)
+            }
+        }
+    };
+}
 
 #[cfg(feature = "serde")]
 mod serde_date {
@@ -62,7 +116,7 @@ mod serde_date {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub name: String,
@@ -75,16 +129,26 @@ pub struct Player {
     pub country: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+ord_by_key!(Player, |v| (
+    &v.name,
+    &v.avatar,
+    u64::from(v.steam_id),
+    &v.honors,
+    &v.teams,
+    &v.favorite_classes,
+    &v.country
+));
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Honors {
     pub format: GameMode,
-    pub division: String,
+    pub division: Division,
     pub season: u8,
     pub team: TeamRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamMemberShip {
     pub team: TeamRef,
@@ -93,26 +157,29 @@ pub struct TeamMemberShip {
     pub since: Date,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamRef {
     pub name: String,
     pub id: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MembershipHistory {
-    pub format: String,
+    /// Not set for leagues that aren't a known game mode
+    pub format: Option<GameMode>,
+    /// The league as listed on the website
+    pub league: String,
     pub team: TeamRef,
-    pub division: String,
+    pub division: Division,
     #[cfg_attr(feature = "serde", serde(with = "serde_date"))]
     pub joined: Date,
     #[cfg_attr(feature = "serde", serde(with = "serde_date::opt"))]
     pub left: Option<Date>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Team {
     pub name: String,
@@ -122,7 +189,7 @@ pub struct Team {
     pub region: Option<Region>,
     pub timezone: Option<String>,
     pub steam_group: Option<String>,
-    pub division: String,
+    pub division: Division,
     pub description: String,
     pub titles: Vec<String>,
     pub members: Vec<Membership>,
@@ -130,7 +197,8 @@ pub struct Team {
     pub name_changes: Vec<NameChange>,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Ordered the same as the `player_class` database enum, so sorting in rust and sql agrees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
@@ -148,6 +216,28 @@ pub enum Class {
     Spy,
 }
 
+impl Class {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Class::Scout => "scout",
+            Class::Soldier => "soldier",
+            Class::Pyro => "pyro",
+            Class::Demoman => "demoman",
+            Class::Engineer => "engineer",
+            Class::Heavy => "heavy",
+            Class::Medic => "medic",
+            Class::Sniper => "sniper",
+            Class::Spy => "spy",
+        }
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl FromStr for Class {
     type Err = ();
 
@@ -167,7 +257,105 @@ impl FromStr for Class {
     }
 }
 
-#[derive(Debug, Clone)]
+/// A value that changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(from: T, to: T) -> Option<Self> {
+        (from != to).then_some(Change { from, to })
+    }
+}
+
+/// A member whose role changed between two snapshots of a team
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoleChange {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "serde_steam_id_as_string"))]
+    pub steam_id: SteamID,
+    pub role: Change<MembershipRole>,
+}
+
+ord_by_key!(RoleChange, |v| (&v.name, u64::from(v.steam_id), &v.role));
+
+/// Changes between two snapshots of the same team
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeamDiff {
+    pub added_members: Vec<Membership>,
+    pub removed_members: Vec<Membership>,
+    pub role_changes: Vec<RoleChange>,
+    pub division: Option<Change<Division>>,
+    pub region: Option<Change<Option<Region>>>,
+    pub timezone: Option<Change<Option<String>>>,
+    pub name_changes: Vec<NameChange>,
+}
+
+impl TeamDiff {
+    /// Compare an older snapshot of a team with a newer one.
+    ///
+    /// Members are matched by steam id, so a player renaming themselves isn't reported.
+    pub fn between(old: &Team, new: &Team) -> Self {
+        let find = |members: &[Membership], steam_id: SteamID| {
+            members
+                .iter()
+                .find(|member| member.steam_id == steam_id)
+                .cloned()
+        };
+
+        let added_members = new
+            .members
+            .iter()
+            .filter(|member| find(&old.members, member.steam_id).is_none())
+            .cloned()
+            .collect();
+        let removed_members = old
+            .members
+            .iter()
+            .filter(|member| find(&new.members, member.steam_id).is_none())
+            .cloned()
+            .collect();
+        let role_changes = new
+            .members
+            .iter()
+            .filter_map(|member| {
+                let old_member = find(&old.members, member.steam_id)?;
+                Some(RoleChange {
+                    name: member.name.clone(),
+                    steam_id: member.steam_id,
+                    role: Change::between(old_member.role, member.role)?,
+                })
+            })
+            .collect();
+        let name_changes = new
+            .name_changes
+            .iter()
+            .filter(|change| !old.name_changes.contains(change))
+            .cloned()
+            .collect();
+
+        TeamDiff {
+            added_members,
+            removed_members,
+            role_changes,
+            division: Change::between(old.division.clone(), new.division.clone()),
+            region: Change::between(old.region, new.region),
+            timezone: Change::between(old.timezone.clone(), new.timezone.clone()),
+            name_changes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &TeamDiff::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NameChange {
     pub from_tag: String,
@@ -178,7 +366,7 @@ pub struct NameChange {
     pub date: Date,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Membership {
     pub name: String,
@@ -189,7 +377,14 @@ pub struct Membership {
     pub since: OffsetDateTime,
 }
 
-#[derive(Debug, Clone, Copy)]
+ord_by_key!(Membership, |v| (
+    &v.name,
+    u64::from(v.steam_id),
+    v.role,
+    v.since
+));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
@@ -200,12 +395,21 @@ pub enum MembershipRole {
     Member,
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[error("Invalid membership role {text}")]
 pub struct InvalidMembershipRole {
     pub text: String,
 }
 
+impl Display for MembershipRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MembershipRole::Leader => "leader",
+            MembershipRole::Member => "member",
+        })
+    }
+}
+
 impl FromStr for MembershipRole {
     type Err = InvalidMembershipRole;
 
@@ -232,23 +436,23 @@ impl<'de> serde::Deserialize<'de> for MembershipRole {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record {
     pub season: u32,
-    pub division: String,
+    pub division: Division,
     pub wins: u8,
     pub losses: u8,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamRosterData {
     pub steam_group: Option<String>,
     pub history: Vec<RosterHistory>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RosterHistory {
     pub name: String,
@@ -261,22 +465,31 @@ pub struct RosterHistory {
     pub role: MembershipRole,
 }
 
-#[derive(Debug, Clone)]
+ord_by_key!(RosterHistory, |v| (
+    &v.name,
+    u64::from(v.steam_id),
+    v.joined,
+    v.left,
+    v.role
+));
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamMatches {
     pub team: TeamRef,
     pub seasons: Vec<TeamSeason>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamSeason {
+    pub id: SeasonId,
     pub season: u32,
     pub format: GameMode,
     pub matches: Vec<TeamSeasonMatch>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Side {
@@ -284,7 +497,7 @@ pub enum Side {
     Visiting,
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[error("Invalid side {text}")]
 pub struct InvalidSide {
     pub text: String,
@@ -302,18 +515,19 @@ impl FromStr for Side {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamSeasonMatch {
-    pub division: String,
+    pub division: Division,
     pub week: u8,
-    pub date: String,
+    pub date: MatchDate,
     pub side: Side,
     pub result: MatchResult,
     pub map: String,
 }
 
 impl TeamSeasonMatch {
+    /// Build the match info as seen from `team`, `None` for byes and matches without reported scores
     pub fn match_info(&self, team: &TeamRef, format: GameMode) -> Option<MatchInfo> {
         match &self.result {
             MatchResult::Played {
@@ -334,14 +548,15 @@ impl TeamSeasonMatch {
                 score_opponent,
                 ..
             } => {
+                let (Some(score), Some(score_opponent)) = (*score, *score_opponent) else {
+                    return None;
+                };
                 let (team_home, team_away, score_home, score_away) = if self.side == Side::Home {
-                    (team.clone(), opponent.clone(), *score, *score_opponent)
+                    (team.clone(), opponent.clone(), score, score_opponent)
                 } else {
-                    (opponent.clone(), team.clone(), *score_opponent, *score)
+                    (opponent.clone(), team.clone(), score_opponent, score)
                 };
                 Some(MatchInfo {
-                    comment: None,
-                    comment_author: None,
                     team_home,
                     team_away,
                     score_home,
@@ -350,6 +565,11 @@ impl TeamSeasonMatch {
                     week: self.week,
                     format,
                     default_date: self.date.clone(),
+                    rounds: Vec::new(),
+                    forfeit: None,
+                    no_show: false,
+                    comments: Vec::new(),
+                    links: Vec::new(),
                 })
             }
             _ => None,
@@ -364,26 +584,199 @@ pub enum MatchResult {
     Played {
         id: u32,
         opponent: TeamRef,
-        score: u8,
-        score_opponent: u8,
+        score: Option<u8>,
+        score_opponent: Option<u8>,
         match_points: f32,
         match_points_opponent: f32,
+        outcome: Outcome,
     },
     Pending {
         id: u32,
         opponent: TeamRef,
-        score: u8,
-        score_opponent: u8,
+        score: Option<u8>,
+        score_opponent: Option<u8>,
     },
     ByeWeek,
     Unknown {
         opponent: TeamRef,
-        score: u8,
-        score_opponent: u8,
+        score: Option<u8>,
+        score_opponent: Option<u8>,
     },
 }
 
+impl PartialEq for MatchResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp_key() == other.cmp_key()
+    }
+}
+
+impl Eq for MatchResult {}
+
+impl PartialOrd for MatchResult {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MatchResult {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_key().cmp(&other.cmp_key())
+    }
+}
+
+impl Hash for MatchResult {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cmp_key().hash(state)
+    }
+}
+
+/// Outcome of a played match, from the perspective of the team
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Outcome {
+    Win,
+    Loss,
+    Tie,
+    ForfeitWin,
+    ForfeitLoss,
+    DoubleForfeit,
+    /// Won after the regular rounds ended in a tie
+    GoldenCapWin,
+    /// Lost after the regular rounds ended in a tie
+    GoldenCapLoss,
+    /// Awarded by an admin against the reported scores
+    AdminWin,
+    /// Awarded by an admin against the reported scores
+    AdminLoss,
+}
+
+impl Outcome {
+    /// Determine the outcome from the scores and match points.
+    ///
+    /// `win_points` are the match points for winning in full, as awarded in the same season.
+    ///
+    /// Forfeits are listed with a 0 - 0 score, with the full match points going to the team that
+    /// showed up. Golden cap results have equal scores, with the winner getting less than the
+    /// full points. When the match points contradict the scores, or there are no scores,
+    /// the result was decided by an admin.
+    pub fn from_scores(
+        score: Option<u8>,
+        score_opponent: Option<u8>,
+        match_points: f32,
+        match_points_opponent: f32,
+        win_points: f32,
+    ) -> Self {
+        let by_points = |win, loss, tie| {
+            if match_points > match_points_opponent {
+                win
+            } else if match_points < match_points_opponent {
+                loss
+            } else {
+                tie
+            }
+        };
+        let (score, score_opponent) = match (score, score_opponent) {
+            (Some(score), Some(score_opponent)) => (score, score_opponent),
+            _ => return by_points(Outcome::AdminWin, Outcome::AdminLoss, Outcome::Tie),
+        };
+        let winner_points = match_points.max(match_points_opponent);
+        if score == 0 && score_opponent == 0 {
+            if match_points == 0.0 && match_points_opponent == 0.0 {
+                Outcome::DoubleForfeit
+            } else if winner_points >= win_points {
+                by_points(Outcome::ForfeitWin, Outcome::ForfeitLoss, Outcome::Tie)
+            } else {
+                by_points(Outcome::GoldenCapWin, Outcome::GoldenCapLoss, Outcome::Tie)
+            }
+        } else if score == score_opponent {
+            by_points(Outcome::GoldenCapWin, Outcome::GoldenCapLoss, Outcome::Tie)
+        } else if score > score_opponent {
+            if match_points < match_points_opponent {
+                Outcome::AdminLoss
+            } else {
+                Outcome::Win
+            }
+        } else if match_points > match_points_opponent {
+            Outcome::AdminWin
+        } else {
+            Outcome::Loss
+        }
+    }
+}
+
+type MatchResultKey<'a> = (
+    u8,
+    Option<u32>,
+    Option<&'a TeamRef>,
+    Option<u8>,
+    Option<u8>,
+    TotalF32,
+    TotalF32,
+    Option<Outcome>,
+);
+
 impl MatchResult {
+    fn cmp_key(&self) -> MatchResultKey<'_> {
+        match self {
+            MatchResult::Played {
+                id,
+                opponent,
+                score,
+                score_opponent,
+                match_points,
+                match_points_opponent,
+                outcome,
+            } => (
+                0,
+                Some(*id),
+                Some(opponent),
+                *score,
+                *score_opponent,
+                TotalF32(*match_points),
+                TotalF32(*match_points_opponent),
+                Some(*outcome),
+            ),
+            MatchResult::Pending {
+                id,
+                opponent,
+                score,
+                score_opponent,
+            } => (
+                1,
+                Some(*id),
+                Some(opponent),
+                *score,
+                *score_opponent,
+                TotalF32(0.0),
+                TotalF32(0.0),
+                None,
+            ),
+            MatchResult::ByeWeek => (2, None, None, None, None, TotalF32(0.0), TotalF32(0.0), None),
+            MatchResult::Unknown {
+                opponent,
+                score,
+                score_opponent,
+            } => (
+                3,
+                None,
+                Some(opponent),
+                *score,
+                *score_opponent,
+                TotalF32(0.0),
+                TotalF32(0.0),
+                None,
+            ),
+        }
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self {
+            MatchResult::Played { outcome, .. } => Some(*outcome),
+            _ => None,
+        }
+    }
+
     pub fn match_id(&self) -> Option<u32> {
         match self {
             MatchResult::Played { id, .. } | MatchResult::Pending { id, .. } => Some(*id),
@@ -401,25 +794,160 @@ impl MatchResult {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Seasons {
     pub mode: String,
+    /// Not set when the menu has no seasons with a known game mode
+    pub format: Option<GameMode>,
     pub seasons: Vec<Season>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Season {
-    pub id: String,
+    pub id: SeasonId,
     pub name: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid season id {text}")]
+pub struct InvalidSeasonId {
+    pub text: String,
+}
+
+/// Identifies a season across pages, formatted like the season rankings links (`tf2h_season41`).
+///
+/// Only the asian leagues are listed separately on the website, other seasons have no region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeasonId {
+    pub format: GameMode,
+    pub number: u32,
+    pub region: Option<Region>,
+}
+
+impl SeasonId {
+    pub fn new(format: GameMode, number: u32) -> Self {
+        SeasonId {
+            format,
+            number,
+            region: None,
+        }
+    }
+
+    /// Parse a season from a header like "Season 41" or "ASIA HL Season 38"
+    pub fn from_header(format: GameMode, header: &str) -> Result<Self, InvalidSeasonId> {
+        let number = Self::number_from_header(header).ok_or_else(|| InvalidSeasonId {
+            text: header.to_string(),
+        })?;
+        let region = header.contains("ASIA").then_some(Region::Asia);
+        Ok(SeasonId {
+            format,
+            number,
+            region,
+        })
+    }
+
+    /// Get the season number from a header like "Season 41" or "TF2 Highlander Season 41 Matches"
+    pub fn number_from_header(header: &str) -> Option<u32> {
+        let (_, number) = header.rsplit_once("Season")?;
+        let number = number.trim_start();
+        let end = number
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(number.len());
+        number[..end].parse().ok()
+    }
+}
+
+impl FromStr for SeasonId {
+    type Err = InvalidSeasonId;
+
+    /// Parse ids like "tf2h_season41", "atf26_season40" or links like "rankings_ow_season19.cfm"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || InvalidSeasonId {
+            text: s.to_string(),
+        };
+        let id = s.trim_start_matches("rankings_").trim_end_matches(".cfm");
+        let (league, number) = id.split_once("_season").ok_or_else(err)?;
+        let number = number.parse().map_err(|_| err())?;
+        let (region, league) = match league.strip_prefix('a') {
+            Some(league) if league.starts_with("tf2") || league == "ow" => {
+                (Some(Region::Asia), league)
+            }
+            _ => (None, league),
+        };
+        let format = match league {
+            "ow" => GameMode::Overwatch,
+            league => {
+                let letter = league.strip_prefix("tf2").ok_or_else(err)?;
+                [
+                    GameMode::Highlander,
+                    GameMode::Eights,
+                    GameMode::Sixes,
+                    GameMode::Fours,
+                    GameMode::Ultiduo,
+                    GameMode::Ones,
+                ]
+                .into_iter()
+                .find(|format| format.letter() == letter)
+                .ok_or_else(err)?
+            }
+        };
+        Ok(SeasonId {
+            format,
+            number,
+            region,
+        })
+    }
+}
+
+impl Display for SeasonId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.region == Some(Region::Asia) {
+            f.write_str("a")?;
+        }
+        match self.format {
+            GameMode::Overwatch => f.write_str("ow")?,
+            format => write!(f, "tf2{}", format.letter())?,
+        }
+        write!(f, "_season{}", self.number)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SeasonId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SeasonId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+#[test]
+fn test_parse_season_id() {
+    let id: SeasonId = "rankings_atf26_season40.cfm".parse().unwrap();
+    assert_eq!(GameMode::Sixes, id.format);
+    assert_eq!(40, id.number);
+    assert_eq!(Some(Region::Asia), id.region);
+    assert_eq!("atf26_season40", id.to_string());
+    assert_eq!(
+        SeasonId::new(GameMode::Highlander, 41),
+        SeasonId::from_header(GameMode::Highlander, "TF2 Highlander Season 41 Matches").unwrap()
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchInfo {
-    pub comment: Option<String>,
-    pub comment_author: Option<String>,
     pub team_home: TeamRef,
     pub team_away: TeamRef,
     pub score_home: u8,
@@ -427,16 +955,65 @@ pub struct MatchInfo {
     pub map: String,
     pub week: u8,
     pub format: GameMode,
-    pub default_date: String,
+    pub default_date: MatchDate,
+    pub rounds: Vec<RoundScore>,
+    pub forfeit: Option<Forfeit>,
+    pub no_show: bool,
+    pub comments: Vec<MatchComment>,
+    pub links: Vec<ExternalLink>,
+}
+
+/// A link to logs or demos of a match on an external site
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExternalLink {
+    pub kind: ExternalLinkKind,
+    /// Log or demo id for logs.tf and demos.tf, the file name for stv demos
+    pub id: String,
+    pub url: String,
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ExternalLinkKind {
+    Logs,
+    Demos,
+    Stv,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundScore {
+    pub home: u8,
+    pub away: u8,
+}
+
+/// The team(s) that forfeited a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Forfeit {
+    Home,
+    Away,
+    Double,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchComment {
+    pub author: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[error("Invalid game mode {text}")]
 pub struct InvalidGameMode {
     pub text: String,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Ordered the same as the `game_mode` database enum, followed by the formats that aren't archived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
 #[cfg_attr(feature = "sqlx", sqlx(type_name = "game_mode"))]
 #[cfg_attr(feature = "sqlx", sqlx(rename_all = "lowercase"))]
@@ -570,13 +1147,14 @@ impl Display for GameMode {
     }
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[error("Invalid team region: {text}")]
 pub struct InvalidRegion {
     pub text: String,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// Ordered the same as the `region` database enum, with Europe first
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
@@ -619,6 +1197,7 @@ impl FromStr for Region {
             "AUS" => Ok(Region::Australia),
             "AUS/NZ" => Ok(Region::Australia),
             "AUS-NZ" => Ok(Region::Australia),
+            "Australia" => Ok(Region::Australia),
             _ => Err(InvalidRegion {
                 text: s.to_string(),
             }),
@@ -626,7 +1205,187 @@ impl FromStr for Region {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Region::Europe => "Europe",
+            Region::NorthAmerica => "North America",
+            Region::SouthAmerica => "South America",
+            Region::Asia => "Asia",
+            Region::Australia => "Australia",
+        })
+    }
+}
+
+/// Skill tier of a division, from lowest to highest
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Tier {
+    Iron,
+    Steel,
+    Silver,
+    Gold,
+    Platinum,
+    Premium,
+}
+
+impl FromStr for Tier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "iron" => Ok(Tier::Iron),
+            "steel" => Ok(Tier::Steel),
+            "silver" => Ok(Tier::Silver),
+            "gold" => Ok(Tier::Gold),
+            "platinum" => Ok(Tier::Platinum),
+            "premium" => Ok(Tier::Premium),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Tier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Tier::Iron => "Iron",
+            Tier::Steel => "Steel",
+            Tier::Silver => "Silver",
+            Tier::Gold => "Gold",
+            Tier::Platinum => "Platinum",
+            Tier::Premium => "Premium",
+        })
+    }
+}
+
+/// The division a team plays in.
+///
+/// Divisions are ordered by tier, with divisions outside the tier system sorted before all tiers.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Division {
+    /// Divisions outside the tier system, like "Waiting for Placement" or older regional divisions
+    Other(String),
+    Tiered {
+        tier: Tier,
+        region: Option<Region>,
+        /// The division as listed on the website
+        name: String,
+    },
+}
+
+impl Division {
+    pub fn tier(&self) -> Option<Tier> {
+        match self {
+            Division::Tiered { tier, .. } => Some(*tier),
+            Division::Other(_) => None,
+        }
+    }
+
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            Division::Tiered { region, .. } => *region,
+            Division::Other(_) => None,
+        }
+    }
+
+    /// The division as listed on the website
+    pub fn name(&self) -> &str {
+        match self {
+            Division::Tiered { name, .. } => name,
+            Division::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for Division {
+    /// Parse names like "Euro Silver", "Silver N.Amer" or "Premium EU"
+    fn from(s: &str) -> Self {
+        let name = s.trim();
+        let mut tier = None;
+        let mut rest = Vec::new();
+        for word in name.split_whitespace() {
+            match (tier, word.parse::<Tier>()) {
+                (None, Ok(parsed)) => tier = Some(parsed),
+                _ => rest.push(word),
+            }
+        }
+        let rest = rest.join(" ");
+        let region = match rest.as_str() {
+            "" => Ok(None),
+            rest => Region::from_str(rest).map(Some),
+        };
+        match (tier, region) {
+            (Some(tier), Ok(region)) => Division::Tiered {
+                tier,
+                region,
+                name: name.to_string(),
+            },
+            _ => Division::Other(name.to_string()),
+        }
+    }
+}
+
+impl FromStr for Division {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Division::from(s))
+    }
+}
+
+impl Display for Division {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Division {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Division {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Ok(Division::from(s.as_ref()))
+    }
+}
+
+#[test]
+fn test_parse_division() {
+    assert_eq!(
+        Division::Tiered {
+            tier: Tier::Silver,
+            region: Some(Region::NorthAmerica),
+            name: "Silver N.Amer".into(),
+        },
+        Division::from("Silver N.Amer")
+    );
+    assert_eq!(
+        Division::Tiered {
+            tier: Tier::Premium,
+            region: Some(Region::Europe),
+            name: "Premium EU".into(),
+        },
+        Division::from("Premium EU")
+    );
+    assert_eq!(
+        Division::Other("Waiting for Placement".into()),
+        Division::from("Waiting for Placement")
+    );
+    assert_eq!("Gold N.Amer", Division::from("Gold N.Amer").to_string());
+    assert!(Division::from("Euro Platinum") > Division::from("Euro Gold"));
+    assert!(Division::from("NA Iron") > Division::from("Main NA"));
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transaction {
     pub name: String,
@@ -634,16 +1393,38 @@ pub struct Transaction {
     pub steam_id: SteamID,
     pub action: TransactionAction,
     pub team: TeamRef,
+    pub division: Division,
+    /// The transaction feed only lists the day of the transaction
+    #[cfg_attr(feature = "serde", serde(with = "serde_date"))]
+    pub date: Date,
 }
 
-#[derive(Debug, Clone)]
+ord_by_key!(Transaction, |v| (
+    &v.name,
+    u64::from(v.steam_id),
+    &v.action,
+    &v.team,
+    &v.division,
+    v.date
+));
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransactionAction {
     Joined,
     Left,
 }
 
+/// A player joining or leaving a team, as seen in the roster transactions of a format
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RosterEvent {
+    pub format: GameMode,
+    pub transaction: Transaction,
+}
+
 /// Tried to parse in invalid transaction action
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MallFormedTransaction {
     pub text: String,
 }
@@ -662,14 +1443,14 @@ impl FromStr for TransactionAction {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MapHistory {
     pub current: CurrentSeasonMapList,
     pub previous: Vec<PreviousSeasonMapList>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Week<'a> {
     pub season: u8,
@@ -680,7 +1461,10 @@ pub struct Week<'a> {
 }
 
 impl MapHistory {
-    pub fn weeks(&self, current_season_year: u16) -> impl Iterator<Item = Result<Week, Parse>> {
+    pub fn weeks(
+        &self,
+        current_season_year: u16,
+    ) -> impl Iterator<Item = Result<Week<'_>, Parse>> + '_ {
         const CURRENT_DATE_FORMAT: &[FormatItem<'static>] = format_description!("[weekday case_sensitive:false repr:short], [month repr:short] [day padding:none] [year]");
 
         let current_season = self.current.maps.iter().map(move |map| {
@@ -688,10 +1472,13 @@ impl MapHistory {
                 season: self.current.season,
                 week: map.week,
                 map: map.map.as_str(),
-                date: Date::parse(
-                    &format!("{} {current_season_year}", map.date),
-                    CURRENT_DATE_FORMAT,
-                )?,
+                date: match map.date.date {
+                    Some(date) => date,
+                    None => Date::parse(
+                        &format!("{} {current_season_year}", map.date.text),
+                        CURRENT_DATE_FORMAT,
+                    )?,
+                },
             })
         });
         let past_seasons = self
@@ -708,31 +1495,178 @@ impl MapHistory {
             });
         current_season.chain(past_seasons)
     }
+
+    /// Ids of all seasons in the history, the page for each format only lists the seasons of that format
+    pub fn season_ids(&self, format: GameMode) -> impl Iterator<Item = SeasonId> + '_ {
+        std::iter::once(self.current.season)
+            .chain(self.previous.iter().map(|season| season.season))
+            .map(move |season| SeasonId::new(format, season.into()))
+    }
+
+    /// Resolve the dates of the current season to the years closest to `today`
+    pub fn resolve_current_dates(&mut self, today: Date) {
+        for map in self.current.maps.iter_mut() {
+            map.date.resolve_near(today);
+            if let Some(na_date) = map.na_date.as_mut() {
+                na_date.resolve_near(today);
+            }
+        }
+    }
+
+    /// The date a week of a season was scheduled for
+    pub fn week_date(&self, season: u8, week: u8) -> Option<Date> {
+        if self.current.season == season {
+            return self
+                .current
+                .maps
+                .iter()
+                .find(|map| map.week == week)
+                .and_then(|map| map.date.date);
+        }
+        self.previous
+            .iter()
+            .filter(|previous| previous.season == season)
+            .flat_map(|previous| previous.maps.iter())
+            .find(|map| map.week == week)
+            .map(|map| map.date)
+    }
+
+    /// Resolve the year of a match date using the dates the week was scheduled for.
+    ///
+    /// Without a known season, the date is matched against the week in every season.
+    pub fn resolve_date(&self, season: Option<u8>, week: u8, date: &mut MatchDate) {
+        if let Some(season) = season {
+            if let Some(reference) = self.week_date(season, week) {
+                date.resolve_near(reference);
+            }
+            return;
+        }
+
+        let current = self
+            .current
+            .maps
+            .iter()
+            .filter(|map| map.week == week)
+            .filter_map(|map| map.date.date);
+        let previous = self
+            .previous
+            .iter()
+            .flat_map(|previous| previous.maps.iter())
+            .filter(|map| map.week == week)
+            .map(|map| map.date);
+        for reference in current.chain(previous) {
+            let mut candidate = date.clone();
+            if let Some(resolved) = candidate.resolve_near(reference) {
+                if (resolved - reference).whole_days().abs() <= 7 {
+                    *date = candidate;
+                    return;
+                }
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+/// A match date as shown on the website, which usually leaves out the year
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchDate {
+    pub text: String,
+    /// Only set once the year is known
+    #[cfg_attr(feature = "serde", serde(with = "serde_date::opt"))]
+    pub date: Option<Date>,
+}
+
+impl MatchDate {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let date = parse_full_match_date(&text);
+        MatchDate { text, date }
+    }
+
+    /// Resolve the year to the one closest to `near` that falls on the listed weekday
+    pub fn resolve_near(&mut self, near: Date) -> Option<Date> {
+        if self.date.is_none() {
+            let (weekday, month, day) = parse_partial_match_date(&self.text)?;
+            self.date = (near.year() - 6..=near.year() + 6)
+                .filter_map(|year| Date::from_calendar_date(year, month, day).ok())
+                .filter(|date| date.weekday() == weekday)
+                .min_by_key(|date| (*date - near).whole_days().abs());
+        }
+        self.date
+    }
+}
+
+/// Parse dates like "Mon Oct 09" or "Sun, Oct 08"
+fn parse_partial_match_date(text: &str) -> Option<(Weekday, Month, u8)> {
+    const FORMAT: &[FormatItem<'static>] = format_description!(
+        "[weekday case_sensitive:false repr:short] [month case_sensitive:false repr:short] [day padding:none]"
+    );
+    let mut parsed = Parsed::new();
+    parsed
+        .parse_items(text.replace(',', "").trim().as_bytes(), FORMAT)
+        .ok()?;
+    Some((parsed.weekday()?, parsed.month()?, parsed.day()?.get()))
+}
+
+/// Parse old dates that include the year, like "Wed, 5/13/09"
+fn parse_full_match_date(text: &str) -> Option<Date> {
+    const FORMAT: &[FormatItem<'static>] = format_description!(
+        "[weekday case_sensitive:false repr:short] [month padding:none]/[day padding:none]/[year repr:last_two]"
+    );
+    let mut parsed = Parsed::new();
+    parsed
+        .parse_items(text.replace(',', "").trim().as_bytes(), FORMAT)
+        .ok()?;
+    let year = parsed.year_last_two()? as i32 + 2000;
+    parsed.set_year(year)?;
+    Date::try_from(parsed).ok()
+}
+
+#[test]
+fn test_parse_old_match_date() {
+    assert_eq!(
+        Date::from_calendar_date(2009, Month::May, 13).ok(),
+        MatchDate::new("Wed, 5/13/09").date
+    );
+}
+
+#[test]
+fn test_resolve_match_date() {
+    let near = Date::from_calendar_date(2019, Month::September, 1).unwrap();
+    assert_eq!(
+        Date::from_calendar_date(2019, Month::October, 6).ok(),
+        MatchDate::new("Sun Oct 06").resolve_near(near)
+    );
+    assert_eq!(
+        Date::from_calendar_date(2023, Month::October, 8).ok(),
+        MatchDate::new("Sun, Oct 08")
+            .resolve_near(Date::from_calendar_date(2024, Month::September, 1).unwrap())
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurrentSeasonMapList {
     pub season: u8,
     pub maps: Vec<CurrentSeasonMap>,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PreviousSeasonMapList {
     pub season: u8,
     pub maps: Vec<PreviousSeasonMap>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurrentSeasonMap {
     pub week: u8,
     pub map: String,
-    pub date: String,
-    pub na_date: Option<String>,
+    pub date: MatchDate,
+    pub na_date: Option<MatchDate>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PreviousSeasonMap {
     pub week: u8,
@@ -751,7 +1685,6 @@ pub mod serde_steam_id_as_string {
         format!("{id}").serialize(serializer)
     }
 
-    #[allow(dead_code)]
     pub fn deserialize<'de, D>(deserializer: D) -> Result<SteamID, D::Error>
     where
         D: Deserializer<'de>,
@@ -759,3 +1692,109 @@ pub mod serde_steam_id_as_string {
         SteamID::deserialize(deserializer)
     }
 }
+
+#[test]
+fn test_outcome_from_scores() {
+    assert_eq!(
+        Outcome::from_scores(Some(4), Some(1), 5.0, 1.25, 5.0),
+        Outcome::Win
+    );
+    assert_eq!(
+        Outcome::from_scores(Some(0), Some(0), 5.0, 0.0, 5.0),
+        Outcome::ForfeitWin
+    );
+    assert_eq!(
+        Outcome::from_scores(Some(0), Some(0), 0.0, 0.0, 5.0),
+        Outcome::DoubleForfeit
+    );
+    assert_eq!(
+        Outcome::from_scores(Some(0), Some(0), 2.5, 0.0, 5.0),
+        Outcome::GoldenCapWin
+    );
+    assert_eq!(
+        Outcome::from_scores(Some(2), Some(2), 3.0, 5.0, 5.0),
+        Outcome::GoldenCapLoss
+    );
+    assert_eq!(
+        Outcome::from_scores(Some(3), Some(1), 0.0, 5.0, 5.0),
+        Outcome::AdminLoss
+    );
+    assert_eq!(
+        Outcome::from_scores(None, None, 5.0, 0.0, 5.0),
+        Outcome::AdminWin
+    );
+}
+
+#[test]
+fn test_canonical_ordering() {
+    let mut regions = vec![Region::Asia, Region::NorthAmerica, Region::Europe];
+    regions.sort();
+    assert_eq!(
+        vec![Region::Europe, Region::NorthAmerica, Region::Asia],
+        regions
+    );
+    assert!(GameMode::Highlander < GameMode::Sixes);
+    assert!(Class::Scout < Class::Spy);
+    assert_eq!("North America", Region::NorthAmerica.to_string());
+    assert_eq!(
+        Ok(Class::Demoman),
+        Class::from_str(&Class::Demoman.to_string())
+    );
+    assert_eq!("leader", MembershipRole::Leader.to_string());
+}
+
+#[test]
+fn test_team_diff() {
+    let member = |name: &str, id: u64, role| Membership {
+        name: name.into(),
+        steam_id: SteamID::from(id),
+        role,
+        since: OffsetDateTime::UNIX_EPOCH,
+    };
+    let old = Team {
+        name: "Xenon".into(),
+        tag: "XE".into(),
+        image: None,
+        format: GameMode::Highlander,
+        region: Some(Region::Europe),
+        timezone: None,
+        steam_group: None,
+        division: "Euro Silver".into(),
+        description: String::new(),
+        titles: Vec::new(),
+        members: vec![
+            member("a", 76561198000000001, MembershipRole::Leader),
+            member("b", 76561198000000002, MembershipRole::Member),
+        ],
+        results: Vec::new(),
+        name_changes: Vec::new(),
+    };
+    assert!(TeamDiff::between(&old, &old).is_empty());
+
+    let new = Team {
+        division: "Euro Gold".into(),
+        members: vec![
+            member("b", 76561198000000002, MembershipRole::Leader),
+            member("c", 76561198000000003, MembershipRole::Member),
+        ],
+        ..old.clone()
+    };
+    let diff = TeamDiff::between(&old, &new);
+    assert_eq!(vec![new.members[1].clone()], diff.added_members);
+    assert_eq!(vec![old.members[0].clone()], diff.removed_members);
+    assert_eq!(
+        Change {
+            from: MembershipRole::Member,
+            to: MembershipRole::Leader
+        },
+        diff.role_changes[0].role
+    );
+    assert_eq!(
+        Some(Change {
+            from: old.division.clone(),
+            to: new.division.clone()
+        }),
+        diff.division
+    );
+    assert_eq!(None, diff.region);
+}